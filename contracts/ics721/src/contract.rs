@@ -1,38 +1,64 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, Binary, Deps, DepsMut, Env, IbcMsg, IbcQuery, MessageInfo, Order,
-    PortIdResponse, Response, StdResult,
+    from_binary, to_binary, Addr, Binary, Deps, DepsMut, Empty, Env, IbcMsg, IbcQuery, IbcTimeout,
+    MessageInfo, Order, PortIdResponse, Response, StdResult, Storage, Timestamp, WasmMsg,
 };
 use cw2::set_contract_version;
-use cw20_ics20::msg::{ListChannelsResponse, PortResponse};
-use cw721::Cw721ReceiveMsg;
-use cw_utils::nonpayable;
+use cw721::{Cw721ExecuteMsg, Cw721QueryMsg, Cw721ReceiveMsg, NftInfoResponse, OwnerOfResponse};
+use cw721_base::MintMsg;
+use cw_storage_plus::Bound;
+use cw_utils::{maybe_addr, nonpayable};
+use sg721::msg::{
+    CollectionInfoResponse, ExecuteMsg as Sg721ExecuteMsg, QueryMsg as Sg721QueryMsg,
+};
 
 use crate::error::ContractError;
 use crate::ibc::Ics721Packet;
-use crate::msg::{ChannelResponse, ExecuteMsg, InstantiateMsg, QueryMsg, TransferMsg};
-use crate::state::{Config, CHANNEL_INFO, CHANNEL_STATE, CONFIG};
+use crate::msg::{
+    ChannelResponse, ClassIdResponse, ClassIdsResponse, CollectionPoliciesResponse,
+    CollectionPolicyInfo, CollectionPolicyResponse, EscrowedByResponse, ExecuteMsg, InstantiateMsg,
+    ListChannelsResponse, PolicyResponse, PortResponse, QueryMsg, RateLimitResponse,
+    TokensResponse, TransferMsg,
+};
+use crate::state::{
+    collection_policy, escrow_token, release_escrow, remove_channel_token, CollectionPolicy,
+    Config, Counterparty, RateLimit, RateLimitUsage, ADMIN, CHANNEL_CLASSES, CHANNEL_INFO,
+    CHANNEL_RATE_LIMITS, CHANNEL_RATE_USAGE, CHANNEL_STATE, CHANNEL_TIMEOUTS, CLASS_ID_TO_CONTRACT,
+    CLASS_RATE_LIMITS, CLASS_RATE_USAGE, CLOSED_CHANNELS, COLLECTION_POLICIES, CONFIG,
+    CONTRACT_TO_CLASS_ID, COUNTERPARTY_ALLOWLIST, DEFAULT_COLLECTION_POLICY, ESCROWS,
+    ESCROW_CHANNELS, PAUSED_CHANNELS,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:sg721-ics721";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
 #[cfg(test)]
 #[path = "contract_test.rs"]
 mod contract_test;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
-    deps: DepsMut,
+    mut deps: DepsMut,
     _env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    let admin = maybe_addr(deps.api, msg.admin)?;
+    ADMIN.set(deps.branch(), admin)?;
     let cfg = Config {
         default_timeout: msg.default_timeout,
+        max_timeout: msg.max_timeout,
+        voucher_code_id: msg.voucher_code_id,
+        voucher_creation_fee: msg.voucher_creation_fee,
     };
+    check_timeout(&cfg, cfg.default_timeout)?;
     CONFIG.save(deps.storage, &cfg)?;
     Ok(Response::default())
 }
@@ -46,10 +72,553 @@ pub fn execute(
 ) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
-        ExecuteMsg::Transfer(msg) => execute_transfer(deps, env, msg, info.sender),
+        ExecuteMsg::MintVouchers {
+            channel_id,
+            class_id,
+            token_ids,
+            token_uris,
+            receiver,
+            callback,
+        } => execute_mint_vouchers(
+            deps, env, info, channel_id, class_id, token_ids, token_uris, receiver, callback,
+        ),
+        ExecuteMsg::ReleaseNfts {
+            channel_id,
+            contract_addr,
+            token_ids,
+            receiver,
+            callback,
+        } => execute_release_nfts(
+            deps,
+            env,
+            info,
+            channel_id,
+            contract_addr,
+            token_ids,
+            receiver,
+            callback,
+        ),
+        ExecuteMsg::RecoverEscrow {
+            channel_id,
+            class_id,
+            token_ids,
+            recipient,
+        } => execute_recover_escrow(deps, info, channel_id, class_id, token_ids, recipient),
+        ExecuteMsg::SetChannelTimeout {
+            channel_id,
+            default_timeout,
+        } => execute_set_channel_timeout(deps, info, channel_id, default_timeout),
+        ExecuteMsg::SetChannelPaused { channel_id, paused } => {
+            execute_set_channel_paused(deps, info, channel_id, paused)
+        }
+        ExecuteMsg::SetCounterpartyAllowlist { counterparties } => {
+            execute_set_counterparty_allowlist(deps, info, counterparties)
+        }
+        ExecuteMsg::SetCollectionPolicy { collection, policy } => {
+            execute_set_collection_policy(deps, info, collection, policy)
+        }
+        ExecuteMsg::SetDefaultCollectionPolicy { policy } => {
+            ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+            DEFAULT_COLLECTION_POLICY.save(deps.storage, &policy)?;
+            Ok(Response::new()
+                .add_attribute("action", "set_default_collection_policy")
+                .add_attribute("policy", policy_name(policy)))
+        }
+        ExecuteMsg::SetChannelRateLimit { channel_id, limit } => {
+            execute_set_channel_rate_limit(deps, info, channel_id, limit)
+        }
+        ExecuteMsg::SetClassRateLimit { class_id, limit } => {
+            execute_set_class_rate_limit(deps, info, class_id, limit)
+        }
+        ExecuteMsg::UpdateAdmin { admin } => {
+            let admin = maybe_addr(deps.api, admin)?;
+            Ok(ADMIN.execute_update_admin(deps, info, admin)?)
+        }
+    }
+}
+
+pub fn execute_set_channel_timeout(
+    deps: DepsMut,
+    info: MessageInfo,
+    channel_id: String,
+    default_timeout: Option<u64>,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    if !CHANNEL_INFO.has(deps.storage, &channel_id) {
+        return Err(ContractError::NoSuchChannel { id: channel_id });
+    }
+
+    match default_timeout {
+        Some(timeout) => {
+            check_timeout(&CONFIG.load(deps.storage)?, timeout)?;
+            CHANNEL_TIMEOUTS.save(deps.storage, &channel_id, &timeout)?;
+        }
+        None => CHANNEL_TIMEOUTS.remove(deps.storage, &channel_id),
+    }
+
+    let default_timeout = channel_timeout(deps.storage, &channel_id)?;
+    Ok(Response::new()
+        .add_attribute("action", "set_channel_timeout")
+        .add_attribute("channel_id", channel_id)
+        .add_attribute("default_timeout", default_timeout.to_string()))
+}
+
+pub fn execute_set_channel_paused(
+    deps: DepsMut,
+    info: MessageInfo,
+    channel_id: String,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    if !CHANNEL_INFO.has(deps.storage, &channel_id) {
+        return Err(ContractError::NoSuchChannel { id: channel_id });
+    }
+
+    if paused {
+        PAUSED_CHANNELS.save(deps.storage, &channel_id, &Empty {})?;
+    } else {
+        PAUSED_CHANNELS.remove(deps.storage, &channel_id);
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_channel_paused")
+        .add_attribute("channel_id", channel_id)
+        .add_attribute("paused", paused.to_string()))
+}
+
+pub fn execute_set_counterparty_allowlist(
+    deps: DepsMut,
+    info: MessageInfo,
+    counterparties: Option<Vec<Counterparty>>,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+
+    let res = Response::new().add_attribute("action", "set_counterparty_allowlist");
+    match counterparties {
+        Some(counterparties) => {
+            COUNTERPARTY_ALLOWLIST.save(deps.storage, &counterparties)?;
+            Ok(res.add_attribute("counterparties", counterparties.len().to_string()))
+        }
+        None => {
+            COUNTERPARTY_ALLOWLIST.remove(deps.storage);
+            Ok(res.add_attribute("counterparties", "any"))
+        }
+    }
+}
+
+pub fn execute_set_collection_policy(
+    deps: DepsMut,
+    info: MessageInfo,
+    collection: String,
+    policy: Option<CollectionPolicy>,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let collection = deps.api.addr_validate(&collection)?;
+
+    match policy {
+        Some(policy) => COLLECTION_POLICIES.save(deps.storage, &collection, &policy)?,
+        None => COLLECTION_POLICIES.remove(deps.storage, &collection),
+    }
+
+    let policy = collection_policy(deps.storage, &collection)?;
+    Ok(Response::new()
+        .add_attribute("action", "set_collection_policy")
+        .add_attribute("collection", collection)
+        .add_attribute("policy", policy_name(policy)))
+}
+
+fn policy_name(policy: CollectionPolicy) -> &'static str {
+    match policy {
+        CollectionPolicy::Allowed => "allowed",
+        CollectionPolicy::Blocked => "blocked",
+    }
+}
+
+pub fn execute_set_channel_rate_limit(
+    deps: DepsMut,
+    info: MessageInfo,
+    channel_id: String,
+    limit: Option<RateLimit>,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    if !CHANNEL_INFO.has(deps.storage, &channel_id) {
+        return Err(ContractError::NoSuchChannel { id: channel_id });
+    }
+
+    match limit {
+        Some(limit) => {
+            check_rate_limit(&limit)?;
+            CHANNEL_RATE_LIMITS.save(deps.storage, &channel_id, &limit)?;
+        }
+        None => CHANNEL_RATE_LIMITS.remove(deps.storage, &channel_id),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_channel_rate_limit")
+        .add_attribute("channel_id", channel_id))
+}
+
+pub fn execute_set_class_rate_limit(
+    deps: DepsMut,
+    info: MessageInfo,
+    class_id: String,
+    limit: Option<RateLimit>,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+
+    match limit {
+        Some(limit) => {
+            check_rate_limit(&limit)?;
+            CLASS_RATE_LIMITS.save(deps.storage, &class_id, &limit)?;
+        }
+        None => CLASS_RATE_LIMITS.remove(deps.storage, &class_id),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_class_rate_limit")
+        .add_attribute("class_id", class_id))
+}
+
+fn check_rate_limit(limit: &RateLimit) -> Result<(), ContractError> {
+    if limit.period == 0 {
+        return Err(ContractError::InvalidRateLimit {});
+    }
+    Ok(())
+}
+
+/// Counts tokens sent or received on the channel, and of the class (by its local class_id),
+/// against their rate limits. Nothing is counted if either limit is exceeded.
+pub fn consume_rate_limits(
+    storage: &mut dyn Storage,
+    now: Timestamp,
+    channel_id: &str,
+    class_id: &str,
+    tokens: u64,
+    sent: bool,
+) -> Result<(), ContractError> {
+    let (channel_usage, class_usage) =
+        next_usage(storage, now, channel_id, class_id, tokens, sent)?;
+    if let Some(usage) = channel_usage {
+        CHANNEL_RATE_USAGE.save(storage, channel_id, &usage)?;
+    }
+    if let Some(usage) = class_usage {
+        CLASS_RATE_USAGE.save(storage, class_id, &usage)?;
+    }
+    Ok(())
+}
+
+/// Fails like `consume_rate_limits` would, without counting the tokens
+pub fn check_rate_limits(
+    storage: &dyn Storage,
+    now: Timestamp,
+    channel_id: &str,
+    class_id: &str,
+    tokens: u64,
+    sent: bool,
+) -> Result<(), ContractError> {
+    next_usage(storage, now, channel_id, class_id, tokens, sent)?;
+    Ok(())
+}
+
+// the usage of the channel and class limits with the tokens counted, None if there is no limit
+fn next_usage(
+    storage: &dyn Storage,
+    now: Timestamp,
+    channel_id: &str,
+    class_id: &str,
+    tokens: u64,
+    sent: bool,
+) -> Result<(Option<RateLimitUsage>, Option<RateLimitUsage>), ContractError> {
+    let channel_usage = match CHANNEL_RATE_LIMITS.may_load(storage, channel_id)? {
+        Some(limit) => {
+            let usage = CHANNEL_RATE_USAGE.may_load(storage, channel_id)?;
+            let usage = add_usage(usage, &limit, now, tokens, sent).ok_or_else(|| {
+                ContractError::RateLimitExceeded {
+                    scope: format!("channel {}", channel_id),
+                    max_tokens: limit.max_tokens,
+                    period: limit.period,
+                }
+            })?;
+            Some(usage)
+        }
+        None => None,
+    };
+    let class_usage = match CLASS_RATE_LIMITS.may_load(storage, class_id)? {
+        Some(limit) => {
+            let usage = CLASS_RATE_USAGE.may_load(storage, class_id)?;
+            let usage = add_usage(usage, &limit, now, tokens, sent).ok_or_else(|| {
+                ContractError::RateLimitExceeded {
+                    scope: format!("class {}", class_id),
+                    max_tokens: limit.max_tokens,
+                    period: limit.period,
+                }
+            })?;
+            Some(usage)
+        }
+        None => None,
+    };
+    Ok((channel_usage, class_usage))
+}
+
+/// Gives back the usage of tokens sent at `sent_at` whose transfer failed. Usage counted
+/// in a window that has started over since is already gone, so nothing is refunded then.
+pub fn refund_rate_limits(
+    storage: &mut dyn Storage,
+    sent_at: Timestamp,
+    channel_id: &str,
+    class_id: &str,
+    tokens: u64,
+) -> StdResult<()> {
+    if let Some(usage) = CHANNEL_RATE_USAGE.may_load(storage, channel_id)? {
+        CHANNEL_RATE_USAGE.save(storage, channel_id, &refund_usage(usage, sent_at, tokens))?;
+    }
+    if let Some(usage) = CLASS_RATE_USAGE.may_load(storage, class_id)? {
+        CLASS_RATE_USAGE.save(storage, class_id, &refund_usage(usage, sent_at, tokens))?;
+    }
+    Ok(())
+}
+
+// the usage with the sent tokens taken off, if they were counted in its window
+fn refund_usage(mut usage: RateLimitUsage, sent_at: Timestamp, tokens: u64) -> RateLimitUsage {
+    if sent_at.seconds() >= usage.window_start {
+        usage.sent = usage.sent.saturating_sub(tokens);
+    }
+    usage
+}
+
+// the usage with the tokens added, None if that goes over the limit
+fn add_usage(
+    usage: Option<RateLimitUsage>,
+    limit: &RateLimit,
+    now: Timestamp,
+    tokens: u64,
+    sent: bool,
+) -> Option<RateLimitUsage> {
+    let mut usage = current_usage(usage, limit, now);
+    let counted = if sent {
+        &mut usage.sent
+    } else {
+        &mut usage.received
+    };
+    *counted = counted.checked_add(tokens)?;
+    if *counted > limit.max_tokens {
+        return None;
+    }
+    Some(usage)
+}
+
+// the usage in the current window, which starts over once the period has passed
+fn current_usage(
+    usage: Option<RateLimitUsage>,
+    limit: &RateLimit,
+    now: Timestamp,
+) -> RateLimitUsage {
+    match usage {
+        Some(usage) if now.seconds() < usage.window_start.saturating_add(limit.period) => usage,
+        _ => RateLimitUsage {
+            window_start: now.seconds(),
+            sent: 0,
+            received: 0,
+        },
     }
 }
 
+// the timeout in seconds of packets sent on the channel without one
+fn channel_timeout(storage: &dyn Storage, channel_id: &str) -> StdResult<u64> {
+    match CHANNEL_TIMEOUTS.may_load(storage, channel_id)? {
+        Some(timeout) => Ok(timeout),
+        None => Ok(CONFIG.load(storage)?.default_timeout),
+    }
+}
+
+fn check_timeout(config: &Config, timeout: u64) -> Result<(), ContractError> {
+    match config.max_timeout {
+        Some(max) if timeout > max => Err(ContractError::TimeoutTooLong { timeout, max }),
+        _ => Ok(()),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_mint_vouchers(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    channel_id: String,
+    class_id: String,
+    token_ids: Vec<String>,
+    token_uris: Vec<String>,
+    receiver: String,
+    callback: Option<Binary>,
+) -> Result<Response, ContractError> {
+    // only dispatched by ibc_packet_receive, once the voucher collection exists
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+    // counted here so the usage reverts with the mints if they fail
+    consume_rate_limits(
+        deps.storage,
+        env.block.time,
+        &channel_id,
+        &class_id,
+        token_ids.len() as u64,
+        false,
+    )?;
+    let contract_addr = CLASS_ID_TO_CONTRACT
+        .may_load(deps.storage, &class_id)?
+        .ok_or(ContractError::NoVoucherContract {
+            class_id: class_id.clone(),
+        })?;
+    let receiver = deps.api.addr_validate(&receiver)?;
+    // vouchers with a callback are minted to this contract, then sent on with it
+    let owner = match callback {
+        Some(_) => env.contract.address.clone(),
+        None => receiver.clone(),
+    };
+
+    let mut msgs = vec![];
+    for (i, token_id) in token_ids.iter().enumerate() {
+        let mint = Sg721ExecuteMsg::Mint(MintMsg {
+            token_id: token_id.clone(),
+            owner: owner.to_string(),
+            token_uri: token_uris.get(i).filter(|uri| !uri.is_empty()).cloned(),
+            extension: Empty {},
+        });
+        msgs.push(WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_binary(&mint)?,
+            funds: vec![],
+        });
+        if let Some(msg) = &callback {
+            msgs.push(send_nft_msg(&contract_addr, token_id, &receiver, msg)?);
+        }
+    }
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attribute("action", "mint_vouchers")
+        .add_attribute("class_id", class_id)
+        .add_attribute("contract_addr", contract_addr)
+        .add_attribute("receiver", receiver)
+        .add_attribute("token_ids", token_ids.join(",")))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_release_nfts(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    channel_id: String,
+    contract_addr: String,
+    token_ids: Vec<String>,
+    receiver: String,
+    callback: Option<Binary>,
+) -> Result<Response, ContractError> {
+    // only dispatched by ibc_packet_receive, after checking the NFTs were sent over the channel
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+    // counted here so the usage reverts with the transfers if they fail
+    consume_rate_limits(
+        deps.storage,
+        env.block.time,
+        &channel_id,
+        &contract_addr,
+        token_ids.len() as u64,
+        false,
+    )?;
+    let receiver = deps.api.addr_validate(&receiver)?;
+
+    let mut msgs = vec![];
+    for token_id in &token_ids {
+        remove_channel_token(deps.storage, &channel_id, &contract_addr, token_id)?;
+        release_escrow(deps.storage, &channel_id, &contract_addr, token_id);
+        let msg = match &callback {
+            Some(msg) => send_nft_msg(&contract_addr, token_id, &receiver, msg)?,
+            None => WasmMsg::Execute {
+                contract_addr: contract_addr.clone(),
+                msg: to_binary(&Cw721ExecuteMsg::TransferNft {
+                    recipient: receiver.to_string(),
+                    token_id: token_id.clone(),
+                })?,
+                funds: vec![],
+            },
+        };
+        msgs.push(msg);
+    }
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attribute("action", "release_nfts")
+        .add_attribute("channel_id", channel_id)
+        .add_attribute("contract_addr", contract_addr)
+        .add_attribute("receiver", receiver)
+        .add_attribute("token_ids", token_ids.join(",")))
+}
+
+// sends an NFT held by this contract to a contract, calling its ReceiveNft hook with msg
+fn send_nft_msg(
+    contract_addr: &str,
+    token_id: &str,
+    receiver: &Addr,
+    msg: &Binary,
+) -> StdResult<WasmMsg> {
+    Ok(WasmMsg::Execute {
+        contract_addr: contract_addr.to_string(),
+        msg: to_binary(&Cw721ExecuteMsg::SendNft {
+            contract: receiver.to_string(),
+            token_id: token_id.to_string(),
+            msg: msg.clone(),
+        })?,
+        funds: vec![],
+    })
+}
+
+/// Only the admin can recover escrowed NFTs, and only once the channel is closed
+/// so the tokens can no longer come back over it
+pub fn execute_recover_escrow(
+    deps: DepsMut,
+    info: MessageInfo,
+    channel_id: String,
+    class_id: String,
+    token_ids: Vec<String>,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    if !CLOSED_CHANNELS.has(deps.storage, &channel_id) {
+        return Err(ContractError::ChannelOpen { id: channel_id });
+    }
+    let recipient = deps.api.addr_validate(&recipient)?;
+    let contract_addr = match CLASS_ID_TO_CONTRACT.may_load(deps.storage, &class_id)? {
+        Some(contract_addr) => contract_addr.to_string(),
+        None => class_id.clone(),
+    };
+
+    let mut msgs = vec![];
+    for token_id in &token_ids {
+        if !ESCROWS.has(deps.storage, (&channel_id, &class_id, token_id)) {
+            return Err(ContractError::NoSuchNft { class_id });
+        }
+        release_escrow(deps.storage, &channel_id, &class_id, token_id);
+        remove_channel_token(deps.storage, &channel_id, &class_id, token_id)?;
+        msgs.push(WasmMsg::Execute {
+            contract_addr: contract_addr.clone(),
+            msg: to_binary(&Cw721ExecuteMsg::TransferNft {
+                recipient: recipient.to_string(),
+                token_id: token_id.clone(),
+            })?,
+            funds: vec![],
+        });
+    }
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attribute("action", "recover_escrow")
+        .add_attribute("channel_id", channel_id)
+        .add_attribute("class_id", class_id)
+        .add_attribute("recipient", recipient)
+        .add_attribute("token_ids", token_ids.join(",")))
+}
+
 pub fn execute_receive(
     deps: DepsMut,
     env: Env,
@@ -59,50 +628,154 @@ pub fn execute_receive(
     nonpayable(&info)?;
 
     let msg: TransferMsg = from_binary(&wrapper.msg)?;
-    let api = deps.api;
-    execute_transfer(deps, env, msg, api.addr_validate(&wrapper.sender)?)
+    let sender = deps.api.addr_validate(&wrapper.sender)?;
+
+    // anyone can call Receive, so make sure the token was actually handed over
+    let owner: OwnerOfResponse = deps.querier.query_wasm_smart(
+        &info.sender,
+        &Cw721QueryMsg::OwnerOf {
+            token_id: wrapper.token_id.clone(),
+            include_expired: None,
+        },
+    )?;
+    if owner.owner != env.contract.address.as_str() {
+        return Err(ContractError::NotEscrowed {
+            token_id: wrapper.token_id,
+        });
+    }
+
+    // vouchers travel under the class they were received with and can always go back,
+    // NFTs native to this chain under their collection address if it may be bridged
+    let class_id = match CONTRACT_TO_CLASS_ID.may_load(deps.storage, &info.sender)? {
+        Some(class_id) => {
+            // the class_id is "port/channel/..." of the channel the voucher came from,
+            // over any other channel the success ack would burn a voucher that can't be redeemed
+            if class_id.splitn(3, '/').nth(1) != Some(msg.channel.as_str()) {
+                return Err(ContractError::WrongVoucherChannel {
+                    class_id,
+                    channel: msg.channel,
+                });
+            }
+            class_id
+        }
+        None => {
+            if collection_policy(deps.storage, &info.sender)? == CollectionPolicy::Blocked {
+                return Err(ContractError::CollectionBlocked {
+                    collection: info.sender.to_string(),
+                });
+            }
+            info.sender.to_string()
+        }
+    };
+
+    // fill in metadata the sender left out from the collection itself,
+    // collections that don't answer these queries are sent without it
+    let mut msg = msg;
+    if msg.token_uri.is_none() {
+        let nft_info: Option<NftInfoResponse<Empty>> = deps
+            .querier
+            .query_wasm_smart(
+                &info.sender,
+                &Cw721QueryMsg::NftInfo {
+                    token_id: wrapper.token_id.clone(),
+                },
+            )
+            .ok();
+        msg.token_uri = nft_info.and_then(|nft_info| nft_info.token_uri);
+    }
+    let collection_info: Option<CollectionInfoResponse> = deps
+        .querier
+        .query_wasm_smart(&info.sender, &Sg721QueryMsg::CollectionInfo {})
+        .ok();
+    if msg.class_uri.is_none() {
+        msg.class_uri = collection_info
+            .as_ref()
+            .and_then(|collection_info| collection_info.external_link.clone());
+    }
+    let class_data = collection_info.as_ref().map(to_binary).transpose()?;
+
+    execute_transfer(
+        deps,
+        env,
+        msg,
+        class_id,
+        class_data,
+        wrapper.token_id,
+        sender,
+    )
 }
 
 pub fn execute_transfer(
     deps: DepsMut,
     env: Env,
     msg: TransferMsg,
+    class_id: String,
+    class_data: Option<Binary>,
+    token_id: String,
     sender: Addr,
 ) -> Result<Response, ContractError> {
     // ensure the requested channel is registered
     if !CHANNEL_INFO.has(deps.storage, &msg.channel) {
         return Err(ContractError::NoSuchChannel { id: msg.channel });
     };
+    if CLOSED_CHANNELS.has(deps.storage, &msg.channel) {
+        return Err(ContractError::ChannelClosed { id: msg.channel });
+    }
+    if PAUSED_CHANNELS.has(deps.storage, &msg.channel) {
+        return Err(ContractError::ChannelPaused { id: msg.channel });
+    }
 
     // delta from user is in seconds
     let timeout_delta = match msg.timeout {
         Some(t) => t,
-        None => CONFIG.load(deps.storage)?.default_timeout,
+        None => channel_timeout(deps.storage, &msg.channel)?,
+    };
+    check_timeout(&CONFIG.load(deps.storage)?, timeout_delta)?;
+    let timestamp = env.block.time.plus_seconds(timeout_delta);
+    // the packet times out at whichever comes first
+    let timeout = match msg.timeout_block.clone() {
+        Some(block) => IbcTimeout::with_both(block, timestamp),
+        None => IbcTimeout::with_timestamp(timestamp),
     };
-    // timeout is in nanoseconds
-    let timeout = env.block.time.plus_nanos(timeout_delta);
 
     // build ics721 packet
-    let packet = Ics721Packet::new(
-        env.contract.address.as_ref(),
-        None,
-        msg.token_ids
-            .iter()
-            .map(|s| s.as_ref())
-            .collect::<Vec<&str>>(),
-        msg.token_uris
-            .iter()
-            .map(|s| s.as_ref())
-            .collect::<Vec<&str>>(),
-        sender.as_ref(),
-        &msg.remote_address,
-    );
+    let packet = Ics721Packet {
+        memo: msg.memo.clone(),
+        ..Ics721Packet::new(
+            &class_id,
+            msg.class_uri.as_deref(),
+            class_data,
+            vec![token_id.as_str()],
+            vec![msg.token_uri.as_deref().unwrap_or_default()],
+            sender.as_ref(),
+            &msg.remote_address,
+        )
+    };
     packet.validate()?;
 
+    consume_rate_limits(
+        deps.storage,
+        env.block.time,
+        &msg.channel,
+        &class_id,
+        packet.token_ids.len() as u64,
+        true,
+    )?;
+
+    // held until the packet is acknowledged, returned to the sender if it fails
+    escrow_token(
+        deps.storage,
+        &msg.channel,
+        &class_id,
+        &token_id,
+        &sender,
+        env.block.time,
+    )?;
+
     let msg = IbcMsg::SendPacket {
         channel_id: msg.channel,
         data: to_binary(&packet)?,
-        timeout: timeout.into(),
+        timeout,
     };
 
     // Note: we update local state when we get ack - do not count this transfer towards anything until acked
@@ -122,7 +795,7 @@ pub fn execute_transfer(
 // TODO: Alot of this query code is copy pasta.
 // Find a way to make it generic or put into a package.
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Port {} => to_binary(&query_port(deps)?),
         QueryMsg::ListChannels {} => to_binary(&query_list(deps)?),
@@ -130,7 +803,40 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::Tokens {
             channel_id,
             class_id,
-        } => to_binary(&query_tokens(deps, channel_id, class_id)?),
+            start_after,
+            limit,
+        } => to_binary(&query_tokens(
+            deps,
+            channel_id,
+            class_id,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::ClassIds {
+            channel_id,
+            start_after,
+            limit,
+        } => to_binary(&query_class_ids(deps, channel_id, start_after, limit)?),
+        QueryMsg::EscrowedBy { class_id, token_id } => {
+            to_binary(&query_escrowed_by(deps, class_id, token_id)?)
+        }
+        QueryMsg::ClassIdForContract { contract } => {
+            to_binary(&query_class_id_for_contract(deps, contract)?)
+        }
+        QueryMsg::Policy {} => to_binary(&query_policy(deps)?),
+        QueryMsg::CollectionPolicy { collection } => {
+            to_binary(&query_collection_policy(deps, collection)?)
+        }
+        QueryMsg::CollectionPolicies { start_after, limit } => {
+            to_binary(&query_collection_policies(deps, start_after, limit)?)
+        }
+        QueryMsg::ChannelRateLimit { channel_id } => {
+            to_binary(&query_channel_rate_limit(deps, env, channel_id)?)
+        }
+        QueryMsg::ClassRateLimit { class_id } => {
+            to_binary(&query_class_rate_limit(deps, env, class_id)?)
+        }
+        QueryMsg::Admin {} => to_binary(&ADMIN.query_admin(deps)?),
     }
 }
 
@@ -152,34 +858,144 @@ fn query_list(deps: Deps) -> StdResult<ListChannelsResponse> {
 
 pub fn query_channel(deps: Deps, id: String) -> StdResult<ChannelResponse> {
     let info = CHANNEL_INFO.load(deps.storage, &id)?;
-    let _class_ids: StdResult<Vec<_>> = CHANNEL_STATE
-        .sub_prefix(&id)
-        .range(deps.storage, None, None, Order::Ascending)
-        .map(|r| {
-            let (class_id_token_id, _) = r?;
-            Ok(class_id_token_id.0)
-        })
-        .collect();
-
-    let class_ids_resp = _class_ids;
-    match class_ids_resp {
-        Ok(mut class_id_vec) => Ok(ChannelResponse {
-            info,
-            class_ids: {
-                class_id_vec.sort();
-                class_id_vec.dedup();
-                class_id_vec
-            },
-        }),
-        Err(msg) => Err(msg),
-    }
+    let closed = CLOSED_CHANNELS.has(deps.storage, &id);
+    let paused = PAUSED_CHANNELS.has(deps.storage, &id);
+    let default_timeout = channel_timeout(deps.storage, &id)?;
+    let class_ids = query_class_ids(deps, id, None, Some(MAX_LIMIT))?.class_ids;
+    Ok(ChannelResponse {
+        info,
+        closed,
+        paused,
+        default_timeout,
+        class_ids,
+    })
 }
 
-// TODO: https://github.com/public-awesome/contracts/issues/59
 pub fn query_tokens(
-    _deps: Deps,
-    _channel_id: String,
-    _class_id: String,
-) -> StdResult<ChannelResponse> {
-    todo!()
+    deps: Deps,
+    channel_id: String,
+    class_id: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<TokensResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    let tokens = CHANNEL_STATE
+        .prefix((&channel_id, &class_id))
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+    Ok(TokensResponse { tokens })
+}
+
+pub fn query_class_ids(
+    deps: Deps,
+    channel_id: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ClassIdsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    let class_ids = CHANNEL_CLASSES
+        .prefix(&channel_id)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+    Ok(ClassIdsResponse { class_ids })
+}
+
+pub fn query_escrowed_by(
+    deps: Deps,
+    class_id: String,
+    token_id: String,
+) -> StdResult<EscrowedByResponse> {
+    let channel_id = ESCROW_CHANNELS.load(deps.storage, (&class_id, &token_id))?;
+    let sender = ESCROWS.load(deps.storage, (&channel_id, &class_id, &token_id))?;
+    Ok(EscrowedByResponse {
+        channel_id,
+        sender: sender.to_string(),
+    })
+}
+
+pub fn query_class_id_for_contract(deps: Deps, contract: String) -> StdResult<ClassIdResponse> {
+    let contract = deps.api.addr_validate(&contract)?;
+    let class_id = CONTRACT_TO_CLASS_ID.may_load(deps.storage, &contract)?;
+    Ok(ClassIdResponse { class_id })
+}
+
+pub fn query_channel_rate_limit(
+    deps: Deps,
+    env: Env,
+    channel_id: String,
+) -> StdResult<RateLimitResponse> {
+    let limit = CHANNEL_RATE_LIMITS.may_load(deps.storage, &channel_id)?;
+    let usage = CHANNEL_RATE_USAGE.may_load(deps.storage, &channel_id)?;
+    Ok(rate_limit_response(limit, usage, env.block.time))
+}
+
+pub fn query_class_rate_limit(
+    deps: Deps,
+    env: Env,
+    class_id: String,
+) -> StdResult<RateLimitResponse> {
+    let limit = CLASS_RATE_LIMITS.may_load(deps.storage, &class_id)?;
+    let usage = CLASS_RATE_USAGE.may_load(deps.storage, &class_id)?;
+    Ok(rate_limit_response(limit, usage, env.block.time))
+}
+
+fn rate_limit_response(
+    limit: Option<RateLimit>,
+    usage: Option<RateLimitUsage>,
+    now: Timestamp,
+) -> RateLimitResponse {
+    // nothing is counted without a limit
+    let usage = match &limit {
+        Some(limit) => current_usage(usage, limit, now),
+        None => RateLimitUsage::default(),
+    };
+    RateLimitResponse { limit, usage }
+}
+
+pub fn query_policy(deps: Deps) -> StdResult<PolicyResponse> {
+    let counterparty_allowlist = COUNTERPARTY_ALLOWLIST.may_load(deps.storage)?;
+    let default_collection_policy = DEFAULT_COLLECTION_POLICY
+        .may_load(deps.storage)?
+        .unwrap_or(CollectionPolicy::Allowed);
+    Ok(PolicyResponse {
+        counterparty_allowlist,
+        default_collection_policy,
+    })
+}
+
+pub fn query_collection_policy(
+    deps: Deps,
+    collection: String,
+) -> StdResult<CollectionPolicyResponse> {
+    let collection = deps.api.addr_validate(&collection)?;
+    let policy = collection_policy(deps.storage, &collection)?;
+    Ok(CollectionPolicyResponse { policy })
+}
+
+pub fn query_collection_policies(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<CollectionPoliciesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = maybe_addr(deps.api, start_after)?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let collections = COLLECTION_POLICIES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(collection, policy)| CollectionPolicyInfo {
+                collection: collection.to_string(),
+                policy,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(CollectionPoliciesResponse { collections })
 }
@@ -1,14 +1,16 @@
 #[cfg(test)]
 mod contact_testing {
     use super::super::*;
+    use crate::state::{add_channel_token, remove_channel_token};
     use crate::test_constants::*;
     use crate::test_helpers::*;
 
-    use cosmwasm_std::testing::mock_env;
-    use cosmwasm_std::{from_binary, to_binary, Attribute, Coin, StdError};
-    use cosmwasm_std::{CosmosMsg, IbcEndpoint};
+    use crate::state::ChannelInfo;
+    use cosmwasm_std::testing::{mock_env, mock_info};
+    use cosmwasm_std::{attr, from_binary, to_binary, Attribute, Coin, StdError};
+    use cosmwasm_std::{CosmosMsg, IbcEndpoint, IbcTimeoutBlock};
     use cw2::{get_contract_version, ContractVersion};
-    use cw20_ics20::state::ChannelInfo;
+    use cw_controllers::AdminError;
 
     use cosmwasm_std::testing::mock_dependencies;
 
@@ -52,7 +54,7 @@ mod contact_testing {
             },
         )
         .unwrap_err();
-        assert_eq!(err, StdError::not_found("cw20_ics20::state::ChannelInfo"));
+        assert_eq!(err, StdError::not_found("ics721::state::ChannelInfo"));
     }
 
     #[test]
@@ -112,13 +114,7 @@ mod contact_testing {
 
         let contract_addr = "abc/123/collection-addr";
 
-        CHANNEL_STATE
-            .save(
-                deps.as_mut().storage,
-                ("new-channel-key", contract_addr, "1"),
-                &cosmwasm_std::Empty {},
-            )
-            .unwrap();
+        add_channel_token(deps.as_mut().storage, "new-channel-key", contract_addr, "1").unwrap();
 
         let result = query_channel(deps.as_ref(), "new-channel-key".to_string());
         let expected_response = Ok(ChannelResponse {
@@ -130,6 +126,9 @@ mod contact_testing {
                 },
                 connection_id: "new-channel-connection".to_string(),
             },
+            closed: false,
+            paused: false,
+            default_timeout: DEFAULT_TIMEOUT,
             class_ids: vec!["abc/123/collection-addr".to_string()],
         });
         assert_eq!(result, expected_response);
@@ -153,17 +152,11 @@ mod contact_testing {
 
         let contract_addr = "abc/123/collection-addr";
 
-        CHANNEL_STATE
-            .save(
-                deps.as_mut().storage,
-                ("new-channel-key", contract_addr, "1"),
-                &cosmwasm_std::Empty {},
-            )
-            .unwrap();
+        add_channel_token(deps.as_mut().storage, "new-channel-key", contract_addr, "1").unwrap();
 
         let result = query_channel(deps.as_ref(), "101".to_string());
         let expected_response = Err(StdError::NotFound {
-            kind: "cw20_ics20::state::ChannelInfo".to_string(),
+            kind: "ics721::state::ChannelInfo".to_string(),
         });
         assert_eq!(result, expected_response);
     }
@@ -184,21 +177,9 @@ mod contact_testing {
             .unwrap();
 
         let contract_addr = "abc/123/collection-addr";
-        CHANNEL_STATE
-            .save(
-                deps.as_mut().storage,
-                ("new-channel-key", contract_addr, "1"),
-                &cosmwasm_std::Empty {},
-            )
-            .unwrap();
+        add_channel_token(deps.as_mut().storage, "new-channel-key", contract_addr, "1").unwrap();
 
-        CHANNEL_STATE
-            .save(
-                deps.as_mut().storage,
-                ("new-channel-key", contract_addr, "2"),
-                &cosmwasm_std::Empty {},
-            )
-            .unwrap();
+        add_channel_token(deps.as_mut().storage, "new-channel-key", contract_addr, "2").unwrap();
 
         let result = query_channel(deps.as_ref(), "new-channel-key".to_string());
         let expected_response = Ok(ChannelResponse {
@@ -210,6 +191,9 @@ mod contact_testing {
                 },
                 connection_id: "new-channel-connection".to_string(),
             },
+            closed: false,
+            paused: false,
+            default_timeout: DEFAULT_TIMEOUT,
             class_ids: vec!["abc/123/collection-addr".to_string()],
         });
         assert_eq!(result, expected_response);
@@ -233,21 +217,15 @@ mod contact_testing {
         let contract_addr = "abc/123/collection-addr";
         let contract_addr2 = "abc/456/collection-addr";
 
-        CHANNEL_STATE
-            .save(
-                deps.as_mut().storage,
-                ("new-channel-key", contract_addr, "1"),
-                &cosmwasm_std::Empty {},
-            )
-            .unwrap();
+        add_channel_token(deps.as_mut().storage, "new-channel-key", contract_addr, "1").unwrap();
 
-        CHANNEL_STATE
-            .save(
-                deps.as_mut().storage,
-                ("new-channel-key", contract_addr2, "1"),
-                &cosmwasm_std::Empty {},
-            )
-            .unwrap();
+        add_channel_token(
+            deps.as_mut().storage,
+            "new-channel-key",
+            contract_addr2,
+            "1",
+        )
+        .unwrap();
 
         let result = query_channel(deps.as_ref(), "new-channel-key".to_string());
         let expected_response = Ok(ChannelResponse {
@@ -259,6 +237,9 @@ mod contact_testing {
                 },
                 connection_id: "new-channel-connection".to_string(),
             },
+            closed: false,
+            paused: false,
+            default_timeout: DEFAULT_TIMEOUT,
             class_ids: vec![
                 "abc/123/collection-addr".to_string(),
                 "abc/456/collection-addr".to_string(),
@@ -280,6 +261,10 @@ mod contact_testing {
         };
         let instantiate_msg = InstantiateMsg {
             default_timeout: 1000,
+            max_timeout: None,
+            voucher_code_id: VOUCHER_CODE_ID,
+            voucher_creation_fee: None,
+            admin: None,
         };
 
         let contract_version_before = get_contract_version(&deps.storage).unwrap_err();
@@ -300,6 +285,9 @@ mod contact_testing {
         assert_eq!(contract_version_after, expected_contract_version);
         let expected_config = Some(Config {
             default_timeout: 1000,
+            max_timeout: None,
+            voucher_code_id: VOUCHER_CODE_ID,
+            voucher_creation_fee: None,
         });
         assert_eq!(CONFIG.may_load(&deps.storage), Ok(expected_config));
     }
@@ -309,22 +297,21 @@ mod contact_testing {
         let mut deps = setup(&[TEST_CHANNEL_0_DATA, TEST_CHANNEL_1_DATA]);
         let transfer_msg = TransferMsg {
             channel: CHANNEL_FROM_STARS_TO_OMNI.to_string(),
-            class_id: "abc/123/collection-addr".to_string(),
-            class_uri: Some("abc/456/collection-addr".to_string()),
-            token_ids: vec!["1".to_string(), "2".to_string(), "3".to_string()],
-            token_uris: vec![
-                "https://metadata-url.com/my-metadata1".to_string(),
-                "https://metadata-url.com/my-metadata2".to_string(),
-                "https://metadata-url.com/my-metadata3".to_string(),
-            ],
+            class_uri: Some("https://metadata-url.com/collection".to_string()),
+            token_uri: Some("https://metadata-url.com/my-metadata1".to_string()),
             remote_address: "stars1zedxv25ah8fksmg2lzrndrpkvsjqgk4zt5ff7n".to_string(),
             timeout: Some(1000),
+            timeout_block: None,
+            memo: Some("gm".to_string()),
         };
         let sender_address: Addr = Addr::unchecked("wasm1fucynrfkrt684pm8jrt8la5h2csvs5cnldcgqc");
         let result = execute_transfer(
             deps.as_mut(),
             mock_env(),
             transfer_msg.clone(),
+            "collection-addr".to_string(),
+            None,
+            "1".to_string(),
             sender_address.clone(),
         );
         let expected_result = [
@@ -342,29 +329,25 @@ mod contact_testing {
             },
             Attribute {
                 key: "class_id".into(),
-                value: "cosmos2contract".into(),
+                value: "collection-addr".into(),
             },
             Attribute {
                 key: "token_ids".into(),
-                value: "1,2,3".into(),
+                value: "1".into(),
             },
         ];
-        let expected_ics721_packet = Ics721Packet::new(
-            mock_env().contract.address.as_ref(),
-            None,
-            transfer_msg
-                .token_ids
-                .iter()
-                .map(|s| s.as_ref())
-                .collect::<Vec<&str>>(),
-            transfer_msg
-                .token_uris
-                .iter()
-                .map(|s| s.as_ref())
-                .collect::<Vec<&str>>(),
-            sender_address.as_ref(),
-            &transfer_msg.clone().remote_address,
-        );
+        let expected_ics721_packet = Ics721Packet {
+            memo: Some("gm".to_string()),
+            ..Ics721Packet::new(
+                "collection-addr",
+                Some("https://metadata-url.com/collection"),
+                None,
+                vec!["1"],
+                vec!["https://metadata-url.com/my-metadata1"],
+                sender_address.as_ref(),
+                &transfer_msg.remote_address,
+            )
+        };
         let result_msg = &result.unwrap();
         let ibc_msg = &result_msg.messages[0].msg;
         match ibc_msg.clone() {
@@ -375,26 +358,527 @@ mod contact_testing {
             _ => panic!("Did not receive a CosmosMsg"),
         }
         assert_eq!(result_msg.attributes, expected_result);
+
+        // the token is held for the sender until the packet is acknowledged
+        let escrow = ESCROWS.may_load(
+            &deps.storage,
+            (CHANNEL_FROM_STARS_TO_OMNI, "collection-addr", "1"),
+        );
+        assert_eq!(escrow, Ok(Some(sender_address)));
+    }
+
+    fn transfer_timeout(
+        deps: DepsMut,
+        timeout: Option<u64>,
+        timeout_block: Option<IbcTimeoutBlock>,
+    ) -> Result<IbcTimeout, ContractError> {
+        let transfer_msg = TransferMsg {
+            channel: CHANNEL_FROM_STARS_TO_OMNI.to_string(),
+            class_uri: None,
+            token_uri: None,
+            remote_address: "omni-rcpt".to_string(),
+            timeout,
+            timeout_block,
+            memo: None,
+        };
+        let res = execute_transfer(
+            deps,
+            mock_env(),
+            transfer_msg,
+            "collection-addr".to_string(),
+            None,
+            "1".to_string(),
+            Addr::unchecked("local-sender"),
+        )?;
+        match &res.messages[0].msg {
+            CosmosMsg::Ibc(IbcMsg::SendPacket { timeout, .. }) => Ok(timeout.clone()),
+            msg => panic!("unexpected message {:?}", msg),
+        }
+    }
+
+    #[test]
+    fn test_execute_transfer_timeouts() {
+        let mut deps = setup(&[TEST_CHANNEL_0_DATA, TEST_CHANNEL_1_DATA]);
+        let now = mock_env().block.time;
+
+        // timeouts are in seconds
+        let timeout = transfer_timeout(deps.as_mut(), None, None).unwrap();
+        assert_eq!(
+            timeout,
+            IbcTimeout::with_timestamp(now.plus_seconds(DEFAULT_TIMEOUT))
+        );
+        let timeout = transfer_timeout(deps.as_mut(), Some(1000), None).unwrap();
+        assert_eq!(timeout, IbcTimeout::with_timestamp(now.plus_seconds(1000)));
+
+        // with a remote block height the packet times out at whichever comes first
+        let block = IbcTimeoutBlock {
+            revision: 2,
+            height: 12345,
+        };
+        let timeout = transfer_timeout(deps.as_mut(), None, Some(block.clone())).unwrap();
+        assert_eq!(
+            timeout,
+            IbcTimeout::with_both(block, now.plus_seconds(DEFAULT_TIMEOUT))
+        );
+
+        // the channel's default timeout is used instead of the contract's
+        CHANNEL_TIMEOUTS
+            .save(&mut deps.storage, CHANNEL_FROM_STARS_TO_OMNI, &60)
+            .unwrap();
+        let timeout = transfer_timeout(deps.as_mut(), None, None).unwrap();
+        assert_eq!(timeout, IbcTimeout::with_timestamp(now.plus_seconds(60)));
+
+        // timeouts above the maximum are rejected
+        CONFIG
+            .update(&mut deps.storage, |mut config| -> StdResult<_> {
+                config.max_timeout = Some(600);
+                Ok(config)
+            })
+            .unwrap();
+        transfer_timeout(deps.as_mut(), Some(600), None).unwrap();
+        let err = transfer_timeout(deps.as_mut(), Some(601), None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ContractError::TimeoutTooLong {
+                timeout: 601,
+                max: 600
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn test_set_channel_timeout() {
+        let mut deps = setup(&[TEST_CHANNEL_0_DATA, TEST_CHANNEL_1_DATA]);
+        CONFIG
+            .update(&mut deps.storage, |mut config| -> StdResult<_> {
+                config.max_timeout = Some(DEFAULT_TIMEOUT * 2);
+                Ok(config)
+            })
+            .unwrap();
+        let set_timeout = |default_timeout| ExecuteMsg::SetChannelTimeout {
+            channel_id: CHANNEL_FROM_STARS_TO_OMNI.to_string(),
+            default_timeout,
+        };
+        let channel_timeout = |deps: Deps| {
+            query_channel(deps, CHANNEL_FROM_STARS_TO_OMNI.to_string())
+                .unwrap()
+                .default_timeout
+        };
+
+        // only the admin can set it
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            set_timeout(Some(60)),
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), AdminError::NotAdmin {}.to_string());
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(ADMIN_ADDR, &[]),
+            set_timeout(Some(60)),
+        )
+        .unwrap();
+        assert_eq!(res.attributes[2], attr("default_timeout", "60"));
+        assert_eq!(channel_timeout(deps.as_ref()), 60);
+
+        // it can't be above the maximum
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(ADMIN_ADDR, &[]),
+            set_timeout(Some(DEFAULT_TIMEOUT * 2 + 1)),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ContractError::TimeoutTooLong {
+                timeout: DEFAULT_TIMEOUT * 2 + 1,
+                max: DEFAULT_TIMEOUT * 2
+            }
+            .to_string()
+        );
+
+        // clearing it goes back to the contract's default
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(ADMIN_ADDR, &[]),
+            set_timeout(None),
+        )
+        .unwrap();
+        assert_eq!(channel_timeout(deps.as_ref()), DEFAULT_TIMEOUT);
+    }
+
+    #[test]
+    fn test_set_channel_paused() {
+        let mut deps = setup(&[TEST_CHANNEL_0_DATA, TEST_CHANNEL_1_DATA]);
+        let set_paused = |paused| ExecuteMsg::SetChannelPaused {
+            channel_id: CHANNEL_FROM_STARS_TO_OMNI.to_string(),
+            paused,
+        };
+
+        // only the admin can pause
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            set_paused(true),
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), AdminError::NotAdmin {}.to_string());
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(ADMIN_ADDR, &[]),
+            set_paused(true),
+        )
+        .unwrap();
+        let channel = query_channel(deps.as_ref(), CHANNEL_FROM_STARS_TO_OMNI.to_string()).unwrap();
+        assert!(channel.paused);
+
+        // nothing can be sent on a paused channel
+        let err = transfer_timeout(deps.as_mut(), None, None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ContractError::ChannelPaused {
+                id: CHANNEL_FROM_STARS_TO_OMNI.to_string()
+            }
+            .to_string()
+        );
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(ADMIN_ADDR, &[]),
+            set_paused(false),
+        )
+        .unwrap();
+        transfer_timeout(deps.as_mut(), None, None).unwrap();
+    }
+
+    #[test]
+    fn test_transfer_rate_limits() {
+        let mut deps = setup(&[TEST_CHANNEL_0_DATA, TEST_CHANNEL_1_DATA]);
+        let limit = RateLimit {
+            max_tokens: 2,
+            period: 100,
+        };
+        let set_channel_limit = |limit| ExecuteMsg::SetChannelRateLimit {
+            channel_id: CHANNEL_FROM_STARS_TO_OMNI.to_string(),
+            limit,
+        };
+
+        // only the admin can set limits, which must have a period
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            set_channel_limit(Some(limit.clone())),
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), AdminError::NotAdmin {}.to_string());
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(ADMIN_ADDR, &[]),
+            set_channel_limit(Some(RateLimit {
+                max_tokens: 2,
+                period: 0,
+            })),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ContractError::InvalidRateLimit {}.to_string()
+        );
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(ADMIN_ADDR, &[]),
+            ExecuteMsg::SetChannelRateLimit {
+                channel_id: "channel-unknown".to_string(),
+                limit: Some(limit.clone()),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ContractError::NoSuchChannel {
+                id: "channel-unknown".to_string()
+            }
+            .to_string()
+        );
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(ADMIN_ADDR, &[]),
+            set_channel_limit(Some(limit.clone())),
+        )
+        .unwrap();
+        assert_eq!(res.attributes[0], attr("action", "set_channel_rate_limit"));
+
+        transfer_timeout(deps.as_mut(), None, None).unwrap();
+        transfer_timeout(deps.as_mut(), None, None).unwrap();
+        let err = transfer_timeout(deps.as_mut(), None, None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ContractError::RateLimitExceeded {
+                scope: format!("channel {}", CHANNEL_FROM_STARS_TO_OMNI),
+                max_tokens: 2,
+                period: 100,
+            }
+            .to_string()
+        );
+        let res = query_channel_rate_limit(
+            deps.as_ref(),
+            mock_env(),
+            CHANNEL_FROM_STARS_TO_OMNI.to_string(),
+        )
+        .unwrap();
+        assert_eq!(res.limit, Some(limit.clone()));
+        assert_eq!(
+            res.usage,
+            RateLimitUsage {
+                window_start: mock_env().block.time.seconds(),
+                sent: 2,
+                received: 0,
+            }
+        );
+
+        // the usage starts over once the period has passed
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(100);
+        let res = query_channel_rate_limit(
+            deps.as_ref(),
+            env.clone(),
+            CHANNEL_FROM_STARS_TO_OMNI.to_string(),
+        )
+        .unwrap();
+        assert_eq!(res.usage.sent, 0);
+        consume_rate_limits(
+            deps.as_mut().storage,
+            env.block.time,
+            CHANNEL_FROM_STARS_TO_OMNI,
+            "collection-addr",
+            1,
+            true,
+        )
+        .unwrap();
+
+        // the class is limited on every channel, and nothing is counted when it trips
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(ADMIN_ADDR, &[]),
+            ExecuteMsg::SetClassRateLimit {
+                class_id: "collection-addr".to_string(),
+                limit: Some(RateLimit {
+                    max_tokens: 1,
+                    period: 100,
+                }),
+            },
+        )
+        .unwrap();
+        assert_eq!(res.attributes[0], attr("action", "set_class_rate_limit"));
+        consume_rate_limits(
+            deps.as_mut().storage,
+            env.block.time,
+            CHANNEL_FROM_STARS_TO_OMNI,
+            "collection-addr",
+            1,
+            true,
+        )
+        .unwrap();
+        let err = consume_rate_limits(
+            deps.as_mut().storage,
+            env.block.time,
+            CHANNEL_FROM_STARS_TO_OMNI,
+            "collection-addr",
+            1,
+            true,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ContractError::RateLimitExceeded {
+                scope: "class collection-addr".to_string(),
+                max_tokens: 1,
+                period: 100,
+            }
+            .to_string()
+        );
+        let res = query_channel_rate_limit(
+            deps.as_ref(),
+            env.clone(),
+            CHANNEL_FROM_STARS_TO_OMNI.to_string(),
+        )
+        .unwrap();
+        assert_eq!(res.usage.sent, 2);
+        let res =
+            query_class_rate_limit(deps.as_ref(), env, "collection-addr".to_string()).unwrap();
+        assert_eq!(res.usage.sent, 1);
+
+        // without limits nothing is counted
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(ADMIN_ADDR, &[]),
+            set_channel_limit(None),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(ADMIN_ADDR, &[]),
+            ExecuteMsg::SetClassRateLimit {
+                class_id: "collection-addr".to_string(),
+                limit: None,
+            },
+        )
+        .unwrap();
+        transfer_timeout(deps.as_mut(), None, None).unwrap();
+        let res = query_channel_rate_limit(
+            deps.as_ref(),
+            mock_env(),
+            CHANNEL_FROM_STARS_TO_OMNI.to_string(),
+        )
+        .unwrap();
+        assert_eq!(res.limit, None);
+        assert_eq!(res.usage, RateLimitUsage::default());
+    }
+
+    #[test]
+    fn test_collection_policy() {
+        let mut deps = setup(&[TEST_CHANNEL_0_DATA, TEST_CHANNEL_1_DATA]);
+        mock_collection(&mut deps, mock_env().contract.address.as_str());
+        let admin = mock_info(ADMIN_ADDR, &[]);
+        let receive = |collection: &str| {
+            let transfer_msg = TransferMsg {
+                channel: CHANNEL_FROM_STARS_TO_OMNI.to_string(),
+                class_uri: None,
+                token_uri: None,
+                remote_address: "omni-rcpt".to_string(),
+                timeout: None,
+                timeout_block: None,
+                memo: None,
+            };
+            let msg = ExecuteMsg::Receive(Cw721ReceiveMsg {
+                sender: "local-sender".to_string(),
+                token_id: "1".to_string(),
+                msg: to_binary(&transfer_msg).unwrap(),
+            });
+            (mock_info(collection, &[]), msg)
+        };
+        let blocked = |collection: &str| {
+            ContractError::CollectionBlocked {
+                collection: collection.to_string(),
+            }
+            .to_string()
+        };
+
+        // the creator of collection-addr opted out
+        let msg = ExecuteMsg::SetCollectionPolicy {
+            collection: "collection-addr".to_string(),
+            policy: Some(CollectionPolicy::Blocked),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), AdminError::NotAdmin {}.to_string());
+        execute(deps.as_mut(), mock_env(), admin.clone(), msg).unwrap();
+
+        let (info, msg) = receive("collection-addr");
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err.to_string(), blocked("collection-addr"));
+        let (info, msg) = receive("other-collection");
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // vouchers can always go back to their origin
+        CONTRACT_TO_CLASS_ID
+            .save(
+                &mut deps.storage,
+                &Addr::unchecked("voucher-addr"),
+                &format!(
+                    "{}/{}/omni-collection",
+                    CONTRACT_PORT, CHANNEL_FROM_STARS_TO_OMNI
+                ),
+            )
+            .unwrap();
+        let msg = ExecuteMsg::SetDefaultCollectionPolicy {
+            policy: CollectionPolicy::Blocked,
+        };
+        execute(deps.as_mut(), mock_env(), admin.clone(), msg).unwrap();
+        let (info, msg) = receive("voucher-addr");
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // with everything blocked by default, collections have to be allowed
+        let (info, msg) = receive("allowed-collection");
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err.to_string(), blocked("allowed-collection"));
+        let msg = ExecuteMsg::SetCollectionPolicy {
+            collection: "allowed-collection".to_string(),
+            policy: Some(CollectionPolicy::Allowed),
+        };
+        execute(deps.as_mut(), mock_env(), admin, msg).unwrap();
+        let (info, msg) = receive("allowed-collection");
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let res = query_policy(deps.as_ref()).unwrap();
+        assert_eq!(
+            res,
+            PolicyResponse {
+                counterparty_allowlist: None,
+                default_collection_policy: CollectionPolicy::Blocked,
+            }
+        );
+        let res = query_collection_policy(deps.as_ref(), "other-collection".into()).unwrap();
+        assert_eq!(res.policy, CollectionPolicy::Blocked);
+        let res = query_collection_policies(deps.as_ref(), None, None).unwrap();
+        assert_eq!(
+            res.collections,
+            vec![
+                CollectionPolicyInfo {
+                    collection: "allowed-collection".to_string(),
+                    policy: CollectionPolicy::Allowed,
+                },
+                CollectionPolicyInfo {
+                    collection: "collection-addr".to_string(),
+                    policy: CollectionPolicy::Blocked,
+                },
+            ]
+        );
+        let res =
+            query_collection_policies(deps.as_ref(), Some("allowed-collection".into()), Some(1))
+                .unwrap();
+        assert_eq!(res.collections.len(), 1);
+        assert_eq!(res.collections[0].collection, "collection-addr");
     }
 
     #[test]
     fn test_execute_receive_success() {
         let mut deps = setup(&[TEST_CHANNEL_0_DATA, TEST_CHANNEL_1_DATA]);
+        mock_collection(&mut deps, mock_env().contract.address.as_str());
         let sender_address_str = "wasm1fucynrfkrt684pm8jrt8la5h2csvs5cnldcgqc";
-        let sender_address: Addr = Addr::unchecked(sender_address_str);
 
         let transfer_msg = TransferMsg {
             channel: CHANNEL_FROM_STARS_TO_OMNI.to_string(),
-            class_id: "abc/123/collection-addr".to_string(),
-            class_uri: Some("abc/456/collection-addr".to_string()),
-            token_ids: vec!["1".to_string(), "2".to_string(), "3".to_string()],
-            token_uris: vec![
-                "https://metadata-url.com/my-metadata1".to_string(),
-                "https://metadata-url.com/my-metadata2".to_string(),
-                "https://metadata-url.com/my-metadata3".to_string(),
-            ],
+            class_uri: None,
+            token_uri: Some("https://metadata-url.com/my-metadata1".to_string()),
             remote_address: "stars1zedxv25ah8fksmg2lzrndrpkvsjqgk4zt5ff7n".to_string(),
             timeout: Some(1000),
+            timeout_block: None,
+            memo: None,
         };
 
         let cw721_receive_msg = Cw721ReceiveMsg {
@@ -403,10 +887,10 @@ mod contact_testing {
             msg: to_binary(&transfer_msg).unwrap(),
         };
 
-        let initial_funds = vec![];
+        // the collection contract calls Receive when the token is sent to us
         let info_msg: MessageInfo = MessageInfo {
-            sender: sender_address,
-            funds: initial_funds,
+            sender: Addr::unchecked("collection-addr"),
+            funds: vec![],
         };
 
         let result = execute_receive(deps.as_mut(), mock_env(), info_msg, cw721_receive_msg);
@@ -425,28 +909,22 @@ mod contact_testing {
             },
             Attribute {
                 key: "class_id".into(),
-                value: "cosmos2contract".into(),
+                value: "collection-addr".into(),
             },
             Attribute {
                 key: "token_ids".into(),
-                value: "1,2,3".into(),
+                value: "1".into(),
             },
         ];
+        // the class metadata is filled in from the collection
         let expected_ics721_packet = Ics721Packet::new(
-            mock_env().contract.address.as_ref(),
-            None,
-            transfer_msg
-                .token_ids
-                .iter()
-                .map(|s| s.as_ref())
-                .collect::<Vec<&str>>(),
-            transfer_msg
-                .token_uris
-                .iter()
-                .map(|s| s.as_ref())
-                .collect::<Vec<&str>>(),
+            "collection-addr",
+            mock_collection_info().external_link.as_deref(),
+            Some(to_binary(&mock_collection_info()).unwrap()),
+            vec!["1"],
+            vec!["https://metadata-url.com/my-metadata1"],
             sender_address_str,
-            &transfer_msg.clone().remote_address,
+            &transfer_msg.remote_address,
         );
         let result_msg = &result.unwrap();
         let ibc_msg = &result_msg.messages[0].msg;
@@ -460,26 +938,175 @@ mod contact_testing {
 
         assert_eq!(result_msg.attributes, expected_result);
         assert_eq!(result_msg.messages.len(), 1);
+        let escrow = ESCROWS.may_load(
+            &deps.storage,
+            (CHANNEL_FROM_STARS_TO_OMNI, "collection-addr", "1"),
+        );
+        assert_eq!(escrow, Ok(Some(Addr::unchecked(sender_address_str))));
+    }
+
+    #[test]
+    fn test_execute_receive_voucher_success() {
+        let mut deps = setup(&[TEST_CHANNEL_0_DATA, TEST_CHANNEL_1_DATA]);
+        mock_collection(&mut deps, mock_env().contract.address.as_str());
+        let class_id = format!(
+            "{}/{}/omni-collection",
+            CONTRACT_PORT, CHANNEL_FROM_STARS_TO_OMNI
+        );
+        CONTRACT_TO_CLASS_ID
+            .save(
+                &mut deps.storage,
+                &Addr::unchecked("voucher-addr"),
+                &class_id,
+            )
+            .unwrap();
+
+        let transfer_msg = TransferMsg {
+            channel: CHANNEL_FROM_STARS_TO_OMNI.to_string(),
+            class_uri: None,
+            token_uri: None,
+            remote_address: "omni-rcpt".to_string(),
+            timeout: None,
+            timeout_block: None,
+            memo: None,
+        };
+        let msg = ExecuteMsg::Receive(Cw721ReceiveMsg {
+            sender: "local-sender".to_string(),
+            token_id: "1".to_string(),
+            msg: to_binary(&transfer_msg).unwrap(),
+        });
+        let info = MessageInfo {
+            sender: Addr::unchecked("voucher-addr"),
+            funds: vec![],
+        };
+
+        // vouchers go back under the class they were received with
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            res.attributes[3],
+            Attribute {
+                key: "class_id".into(),
+                value: class_id.clone(),
+            }
+        );
+        let escrow = ESCROWS.may_load(&deps.storage, (CHANNEL_FROM_STARS_TO_OMNI, &class_id, "1"));
+        assert_eq!(escrow, Ok(Some(Addr::unchecked("local-sender"))));
+
+        // but never over another channel, where the ack would burn them
+        let transfer_msg = TransferMsg {
+            channel: CHANNEL_FROM_STARS_TO_GB.to_string(),
+            ..transfer_msg
+        };
+        let msg = ExecuteMsg::Receive(Cw721ReceiveMsg {
+            sender: "local-sender".to_string(),
+            token_id: "2".to_string(),
+            msg: to_binary(&transfer_msg).unwrap(),
+        });
+        let info = mock_info("voucher-addr", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ContractError::WrongVoucherChannel {
+                class_id: class_id.clone(),
+                channel: CHANNEL_FROM_STARS_TO_GB.to_string(),
+            }
+            .to_string()
+        );
+        assert!(!ESCROWS.has(&deps.storage, (CHANNEL_FROM_STARS_TO_GB, &class_id, "2")));
+    }
+
+    #[test]
+    fn test_execute_receive_populates_metadata() {
+        let mut deps = setup(&[TEST_CHANNEL_0_DATA, TEST_CHANNEL_1_DATA]);
+        mock_collection(&mut deps, mock_env().contract.address.as_str());
+
+        let transfer_msg = TransferMsg {
+            channel: CHANNEL_FROM_STARS_TO_OMNI.to_string(),
+            class_uri: Some("https://metadata-url.com/collection".to_string()),
+            token_uri: None,
+            remote_address: "omni-rcpt".to_string(),
+            timeout: None,
+            timeout_block: None,
+            memo: None,
+        };
+        let msg = ExecuteMsg::Receive(Cw721ReceiveMsg {
+            sender: "local-sender".to_string(),
+            token_id: "1".to_string(),
+            msg: to_binary(&transfer_msg).unwrap(),
+        });
+        let info = MessageInfo {
+            sender: Addr::unchecked("collection-addr"),
+            funds: vec![],
+        };
+
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let packet: Ics721Packet = match &res.messages[0].msg {
+            CosmosMsg::Ibc(IbcMsg::SendPacket { data, .. }) => from_binary(data).unwrap(),
+            msg => panic!("unexpected message {:?}", msg),
+        };
+        // a class_uri given by the sender is kept, the token uri comes from NftInfo
+        assert_eq!(
+            packet.class_uri,
+            Some("https://metadata-url.com/collection".to_string())
+        );
+        assert_eq!(packet.token_uris, vec![TOKEN_URI.to_string()]);
+        let class_data: CollectionInfoResponse = from_binary(&packet.class_data.unwrap()).unwrap();
+        assert_eq!(class_data, mock_collection_info());
+    }
+
+    #[test]
+    fn test_execute_receive_not_owned_fail() {
+        let mut deps = setup(&[TEST_CHANNEL_0_DATA, TEST_CHANNEL_1_DATA]);
+        // the token was never transferred to this contract
+        mock_collection(&mut deps, "local-sender");
+
+        let transfer_msg = TransferMsg {
+            channel: CHANNEL_FROM_STARS_TO_OMNI.to_string(),
+            class_uri: None,
+            token_uri: None,
+            remote_address: "omni-rcpt".to_string(),
+            timeout: None,
+            timeout_block: None,
+            memo: None,
+        };
+        let msg = ExecuteMsg::Receive(Cw721ReceiveMsg {
+            sender: "local-sender".to_string(),
+            token_id: "1".to_string(),
+            msg: to_binary(&transfer_msg).unwrap(),
+        });
+        let info = MessageInfo {
+            sender: Addr::unchecked("collection-addr"),
+            funds: vec![],
+        };
+
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ContractError::NotEscrowed {
+                token_id: "1".to_string()
+            }
+            .to_string()
+        );
+        let escrow = ESCROWS.may_load(
+            &deps.storage,
+            (CHANNEL_FROM_STARS_TO_OMNI, "collection-addr", "1"),
+        );
+        assert_eq!(escrow, Ok(None));
     }
 
     #[test]
     fn test_execute_receive_nonpayable_fail() {
         let mut deps = setup(&[TEST_CHANNEL_0_DATA, TEST_CHANNEL_1_DATA]);
         let sender_address_str = "wasm1fucynrfkrt684pm8jrt8la5h2csvs5cnldcgqc";
-        let sender_address: Addr = Addr::unchecked(sender_address_str);
 
         let transfer_msg = TransferMsg {
             channel: CHANNEL_FROM_STARS_TO_OMNI.to_string(),
-            class_id: "abc/123/collection-addr".to_string(),
-            class_uri: Some("abc/456/collection-addr".to_string()),
-            token_ids: vec!["1".to_string(), "2".to_string(), "3".to_string()],
-            token_uris: vec![
-                "https://metadata-url.com/my-metadata1".to_string(),
-                "https://metadata-url.com/my-metadata2".to_string(),
-                "https://metadata-url.com/my-metadata3".to_string(),
-            ],
+            class_uri: None,
+            token_uri: Some("https://metadata-url.com/my-metadata1".to_string()),
             remote_address: "stars1zedxv25ah8fksmg2lzrndrpkvsjqgk4zt5ff7n".to_string(),
             timeout: Some(1000),
+            timeout_block: None,
+            memo: None,
         };
 
         let cw721_receive_msg = Cw721ReceiveMsg {
@@ -491,151 +1118,159 @@ mod contact_testing {
         let coin = Coin::new(128, "testing-coin");
         let initial_funds = vec![coin];
         let info_msg: MessageInfo = MessageInfo {
-            sender: sender_address,
+            sender: Addr::unchecked("collection-addr"),
             funds: initial_funds,
         };
-        use cw20_ics20::ContractError::Payment;
         use cw_utils::PaymentError;
         let result =
             execute_receive(deps.as_mut(), mock_env(), info_msg, cw721_receive_msg).unwrap_err();
 
-        let expected_result: cw20_ics20::ContractError = Payment(PaymentError::NonPayable {});
+        let expected_result = ContractError::Payment(PaymentError::NonPayable {});
         assert_eq!(result.to_string(), expected_result.to_string());
     }
 
     #[test]
-    fn test_execute_to_execute_receive_success() {
+    fn test_query_tokens_paginated() {
         let mut deps = setup(&[TEST_CHANNEL_0_DATA, TEST_CHANNEL_1_DATA]);
-        let sender_address_str = "wasm1fucynrfkrt684pm8jrt8la5h2csvs5cnldcgqc";
-        let sender_address: Addr = Addr::unchecked(sender_address_str);
+        for token_id in ["1", "2", "3"] {
+            add_channel_token(
+                deps.as_mut().storage,
+                CHANNEL_FROM_STARS_TO_OMNI,
+                "collection-addr",
+                token_id,
+            )
+            .unwrap();
+        }
+        add_channel_token(
+            deps.as_mut().storage,
+            CHANNEL_FROM_STARS_TO_OMNI,
+            "other-collection",
+            "4",
+        )
+        .unwrap();
 
-        let transfer_msg = TransferMsg {
-            channel: CHANNEL_FROM_STARS_TO_OMNI.to_string(),
-            class_id: "abc/123/collection-addr".to_string(),
-            class_uri: Some("abc/456/collection-addr".to_string()),
-            token_ids: vec!["1".to_string(), "2".to_string(), "3".to_string()],
-            token_uris: vec![
-                "https://metadata-url.com/my-metadata1".to_string(),
-                "https://metadata-url.com/my-metadata2".to_string(),
-                "https://metadata-url.com/my-metadata3".to_string(),
-            ],
-            remote_address: "stars1zedxv25ah8fksmg2lzrndrpkvsjqgk4zt5ff7n".to_string(),
-            timeout: Some(1000),
+        let page = |start_after: Option<&str>| {
+            let raw = query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Tokens {
+                    channel_id: CHANNEL_FROM_STARS_TO_OMNI.to_string(),
+                    class_id: "collection-addr".to_string(),
+                    start_after: start_after.map(String::from),
+                    limit: Some(2),
+                },
+            )
+            .unwrap();
+            from_binary::<TokensResponse>(&raw).unwrap().tokens
         };
-        let cw_721_receive_sender = "sender_address_receive_path";
-        let cw721_receive_msg = ExecuteMsg::Receive(Cw721ReceiveMsg {
-            sender: cw_721_receive_sender.to_string(),
-            token_id: "1".to_string(),
-            msg: to_binary(&transfer_msg).unwrap(),
-        });
+        assert_eq!(page(None), vec!["1".to_string(), "2".to_string()]);
+        assert_eq!(page(Some("2")), vec!["3".to_string()]);
+    }
 
-        let initial_funds = vec![];
-        let info_msg: MessageInfo = MessageInfo {
-            sender: sender_address,
-            funds: initial_funds,
-        };
+    #[test]
+    fn test_query_class_ids() {
+        let mut deps = setup(&[TEST_CHANNEL_0_DATA, TEST_CHANNEL_1_DATA]);
+        let storage = deps.as_mut().storage;
+        add_channel_token(storage, CHANNEL_FROM_STARS_TO_OMNI, "collection-a", "1").unwrap();
+        add_channel_token(storage, CHANNEL_FROM_STARS_TO_OMNI, "collection-a", "2").unwrap();
+        add_channel_token(storage, CHANNEL_FROM_STARS_TO_OMNI, "collection-b", "1").unwrap();
+        add_channel_token(storage, CHANNEL_FROM_STARS_TO_GB, "collection-c", "1").unwrap();
 
-        let result = execute(deps.as_mut(), mock_env(), info_msg, cw721_receive_msg);
-        let expected_result = [
-            Attribute {
-                key: "action".into(),
-                value: "transfer".into(),
-            },
-            Attribute {
-                key: "sender".into(),
-                value: "sender_address_receive_path".into(),
-            },
-            Attribute {
-                key: "receiver".into(),
-                value: "stars1zedxv25ah8fksmg2lzrndrpkvsjqgk4zt5ff7n".into(),
-            },
-            Attribute {
-                key: "class_id".into(),
-                value: "cosmos2contract".into(),
-            },
-            Attribute {
-                key: "token_ids".into(),
-                value: "1,2,3".into(),
-            },
-        ];
+        let res = query_class_ids(
+            deps.as_ref(),
+            CHANNEL_FROM_STARS_TO_OMNI.to_string(),
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(res.class_ids, vec!["collection-a", "collection-b"]);
 
-        let expected_ics721_packet = Ics721Packet::new(
-            mock_env().contract.address.as_ref(),
+        let res = query_class_ids(
+            deps.as_ref(),
+            CHANNEL_FROM_STARS_TO_OMNI.to_string(),
+            Some("collection-a".to_string()),
             None,
-            transfer_msg
-                .token_ids
-                .iter()
-                .map(|s| s.as_ref())
-                .collect::<Vec<&str>>(),
-            transfer_msg
-                .token_uris
-                .iter()
-                .map(|s| s.as_ref())
-                .collect::<Vec<&str>>(),
-            cw_721_receive_sender,
-            &transfer_msg.clone().remote_address,
-        );
-        let result_msg = &result.unwrap();
-        let ibc_msg = &result_msg.messages[0].msg;
-        match ibc_msg.clone() {
-            CosmosMsg::Ibc(IbcMsg::SendPacket { data, .. }) => {
-                let expected_binary = to_binary(&expected_ics721_packet).unwrap();
-                assert_eq!(expected_binary, data);
-            }
-            _ => panic!("Did not receive a CosmosMsg"),
-        }
+        )
+        .unwrap();
+        assert_eq!(res.class_ids, vec!["collection-b"]);
 
-        assert_eq!(result_msg.attributes, expected_result);
-        assert_eq!(result_msg.messages.len(), 1);
+        // a class is listed until its last token comes back
+        let storage = deps.as_mut().storage;
+        remove_channel_token(storage, CHANNEL_FROM_STARS_TO_OMNI, "collection-a", "1").unwrap();
+        let res = query_class_ids(
+            deps.as_ref(),
+            CHANNEL_FROM_STARS_TO_OMNI.to_string(),
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(res.class_ids, vec!["collection-a", "collection-b"]);
+
+        let storage = deps.as_mut().storage;
+        remove_channel_token(storage, CHANNEL_FROM_STARS_TO_OMNI, "collection-a", "2").unwrap();
+        let res = query_class_ids(
+            deps.as_ref(),
+            CHANNEL_FROM_STARS_TO_OMNI.to_string(),
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(res.class_ids, vec!["collection-b"]);
     }
 
     #[test]
-    fn test_execute_to_execute_transfer_success() {
+    fn test_query_escrowed_by() {
         let mut deps = setup(&[TEST_CHANNEL_0_DATA, TEST_CHANNEL_1_DATA]);
-        let transfer_msg = ExecuteMsg::Transfer(TransferMsg {
-            channel: CHANNEL_FROM_STARS_TO_OMNI.to_string(),
-            class_id: "abc/123/collection-addr".to_string(),
-            class_uri: Some("abc/456/collection-addr".to_string()),
-            token_ids: vec!["1".to_string(), "2".to_string(), "3".to_string()],
-            token_uris: vec![
-                "https://metadata-url.com/my-metadata1".to_string(),
-                "https://metadata-url.com/my-metadata2".to_string(),
-                "https://metadata-url.com/my-metadata3".to_string(),
-            ],
-            remote_address: "stars1zedxv25ah8fksmg2lzrndrpkvsjqgk4zt5ff7n".to_string(),
-            timeout: Some(1000),
-        });
-        let sender_address: Addr = Addr::unchecked("wasm1fucynrfkrt684pm8jrt8la5h2csvs5cnldcgqc");
-        let initial_funds = vec![];
-        let info_msg: MessageInfo = MessageInfo {
-            sender: sender_address,
-            funds: initial_funds,
+        let transfer_msg = TransferMsg {
+            channel: CHANNEL_FROM_STARS_TO_GB.to_string(),
+            class_uri: None,
+            token_uri: None,
+            remote_address: "gb-rcpt".to_string(),
+            timeout: None,
+            timeout_block: None,
+            memo: None,
         };
+        execute_transfer(
+            deps.as_mut(),
+            mock_env(),
+            transfer_msg,
+            "collection-addr".to_string(),
+            None,
+            "1".to_string(),
+            Addr::unchecked("local-sender"),
+        )
+        .unwrap();
 
-        let result = execute(deps.as_mut(), mock_env(), info_msg, transfer_msg);
-        let expected_results = [
-            Attribute {
-                key: "action".into(),
-                value: "transfer".into(),
-            },
-            Attribute {
-                key: "sender".into(),
-                value: "wasm1fucynrfkrt684pm8jrt8la5h2csvs5cnldcgqc".into(),
-            },
-            Attribute {
-                key: "receiver".into(),
-                value: "stars1zedxv25ah8fksmg2lzrndrpkvsjqgk4zt5ff7n".into(),
-            },
-            Attribute {
-                key: "class_id".into(),
-                value: "cosmos2contract".into(),
-            },
-            Attribute {
-                key: "token_ids".into(),
-                value: "1,2,3".into(),
-            },
-        ];
-        assert_eq!(result.unwrap().attributes, expected_results);
+        let res = query_escrowed_by(deps.as_ref(), "collection-addr".into(), "1".into()).unwrap();
+        assert_eq!(
+            res,
+            EscrowedByResponse {
+                channel_id: CHANNEL_FROM_STARS_TO_GB.to_string(),
+                sender: "local-sender".to_string(),
+            }
+        );
+        query_escrowed_by(deps.as_ref(), "collection-addr".into(), "2".into()).unwrap_err();
+    }
+
+    #[test]
+    fn test_query_class_id_for_contract() {
+        let mut deps = setup(&[TEST_CHANNEL_0_DATA, TEST_CHANNEL_1_DATA]);
+        let class_id = format!(
+            "{}/{}/omni-collection",
+            CONTRACT_PORT, CHANNEL_FROM_STARS_TO_OMNI
+        );
+        CONTRACT_TO_CLASS_ID
+            .save(
+                &mut deps.storage,
+                &Addr::unchecked("voucher-addr"),
+                &class_id,
+            )
+            .unwrap();
+
+        let res = query_class_id_for_contract(deps.as_ref(), "voucher-addr".into()).unwrap();
+        assert_eq!(res.class_id, Some(class_id));
+        let res = query_class_id_for_contract(deps.as_ref(), "collection-addr".into()).unwrap();
+        assert_eq!(res.class_id, None);
     }
 
     #[test]
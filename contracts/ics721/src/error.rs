@@ -1,5 +1,6 @@
 use cosmwasm_std::StdError;
-use cw_utils::PaymentError;
+use cw_controllers::AdminError;
+use cw_utils::{ParseReplyError, PaymentError};
 use thiserror::Error;
 
 /// Never is a placeholder to ensure we don't return any errors
@@ -14,6 +15,12 @@ pub enum ContractError {
     #[error("{0}")]
     Payment(#[from] PaymentError),
 
+    #[error("{0}")]
+    ParseReply(#[from] ParseReplyError),
+
+    #[error("{0}")]
+    Admin(#[from] AdminError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
@@ -23,6 +30,34 @@ pub enum ContractError {
     #[error("NoSuchChannel")]
     NoSuchChannel { id: String },
 
+    #[error("Channel {id} is closed")]
+    ChannelClosed { id: String },
+
+    #[error("Channel {id} is still open")]
+    ChannelOpen { id: String },
+
+    #[error("Channel {id} is paused")]
+    ChannelPaused { id: String },
+
+    #[error("Channels can't be opened with port {port_id} on connection {connection_id}")]
+    CounterpartyNotAllowed {
+        connection_id: String,
+        port_id: String,
+    },
+
+    #[error("Collection {collection} can't be sent to other chains")]
+    CollectionBlocked { collection: String },
+
+    #[error("Rate limit of {max_tokens} tokens per {period} seconds exceeded for {scope}")]
+    RateLimitExceeded {
+        scope: String,
+        max_tokens: u64,
+        period: u64,
+    },
+
+    #[error("Rate limit period must be longer than 0 seconds")]
+    InvalidRateLimit {},
+
     #[error("Only supports channel with ibc version ics721-1, got {version}")]
     InvalidIbcVersion { version: String },
 
@@ -40,4 +75,40 @@ pub enum ContractError {
 
     #[error("NoSuchNft")]
     NoSuchNft { class_id: String },
+
+    #[error("Token {token_id} was not transferred to this contract")]
+    NotEscrowed { token_id: String },
+
+    #[error("Timeout of {timeout} seconds is longer than the maximum of {max}")]
+    TimeoutTooLong { timeout: u64, max: u64 },
+
+    #[error("Packet must transfer at least one token")]
+    NoTokenIds {},
+
+    #[error("Expected {token_ids} token uris, got {token_uris}")]
+    TokenUrisMismatch { token_ids: usize, token_uris: usize },
+
+    #[error("Expected {token_ids} token data, got {token_data}")]
+    TokenDataMismatch { token_ids: usize, token_data: usize },
+
+    #[error("Invalid class_id {class_id}")]
+    InvalidClassId { class_id: String },
+
+    #[error("Invalid token_id {token_id}")]
+    InvalidTokenId { token_id: String },
+
+    #[error("Token {token_id} is transferred more than once")]
+    DuplicateTokenId { token_id: String },
+
+    #[error("Invalid uri {uri}")]
+    InvalidUri { uri: String },
+
+    #[error("{field} is longer than {max} bytes")]
+    FieldTooLong { field: String, max: usize },
+
+    #[error("NoVoucherContract")]
+    NoVoucherContract { class_id: String },
+
+    #[error("Voucher of class {class_id} can only be sent back over the channel it came from, not {channel}")]
+    WrongVoucherChannel { class_id: String, channel: String },
 }
@@ -1,18 +1,30 @@
-use cw20_ics20::ibc::Ics20Ack;
-use cw20_ics20::state::ChannelInfo;
 use cw721::Cw721ExecuteMsg;
+use cw_utils::parse_reply_instantiate_data;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use url::Url;
 
 use cosmwasm_std::{
-    attr, entry_point, from_binary, to_binary, Binary, DepsMut, Empty, Env, IbcBasicResponse,
-    IbcChannel, IbcChannelCloseMsg, IbcChannelConnectMsg, IbcChannelOpenMsg, IbcEndpoint, IbcOrder,
-    IbcPacket, IbcPacketAckMsg, IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcReceiveResponse,
-    Reply, Response, SubMsg, SubMsgResult, WasmMsg,
+    attr, entry_point, from_binary, from_slice, to_binary, Addr, Binary, DepsMut, Empty, Env,
+    IbcBasicResponse, IbcChannel, IbcChannelCloseMsg, IbcChannelConnectMsg, IbcChannelOpenMsg,
+    IbcEndpoint, IbcOrder, IbcPacket, IbcPacketAckMsg, IbcPacketReceiveMsg, IbcPacketTimeoutMsg,
+    IbcReceiveResponse, Reply, ReplyOn, Response, StdResult, Storage, SubMsg, SubMsgResult,
+    WasmMsg,
 };
+use sg721::msg::{
+    CollectionInfoResponse, ExecuteMsg as Sg721ExecuteMsg, InstantiateMsg as Sg721InstantiateMsg,
+};
+use sg721::state::CollectionInfo;
 
+use crate::contract::{check_rate_limits, refund_rate_limits};
 use crate::error::{ContractError, Never};
-use crate::state::{CHANNEL_INFO, CHANNEL_STATE};
+use crate::msg::ExecuteMsg;
+use crate::state::{
+    add_channel_token, release_escrow, ChannelInfo, CHANNEL_INFO, CHANNEL_STATE,
+    CLASS_ID_TO_CONTRACT, CLOSED_CHANNELS, CONFIG, CONTRACT_TO_CLASS_ID, COUNTERPARTY_ALLOWLIST,
+    ESCROWS, ESCROW_SENT_AT, PAUSED_CHANNELS, PENDING_VOUCHER_CLASS_ID,
+};
 
 pub const ICS721_VERSION: &str = "ics721-1";
 pub const ICS721_ORDERING: IbcOrder = IbcOrder::Unordered;
@@ -21,27 +33,61 @@ pub const ICS721_ORDERING: IbcOrder = IbcOrder::Unordered;
 #[path = "ibc_test.rs"]
 mod ibc_test;
 
-// TODO: need to define proto for chain to parse this?
+/// The ICS-721 `NonFungibleTokenPacketData`, encoded as JSON with the field names of the spec.
+/// Empty optional fields are left out, as other implementations do.
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+#[serde(rename_all = "camelCase")]
 pub struct Ics721Packet {
     /// uniquely identifies the collection to which the NFT belongs
     /// the sg721 collection contract address
     pub class_id: String,
     // a link for class/contract-level metadata
     /// https://docs.opensea.io/docs/contract-level-metadata
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub class_uri: Option<String>,
+    /// arbitrary collection metadata, the sg721 CollectionInfo for collections on this chain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub class_data: Option<Binary>,
     /// uniquely identifies NFTs within the collection that is being transferred
     pub token_ids: Vec<String>,
     /// https://docs.opensea.io/docs/metadata-standards
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub token_uris: Vec<String>,
+    /// arbitrary metadata per token, not used by sg721
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub token_data: Vec<Binary>,
     pub sender: String,
     pub receiver: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+/// A packet memo asking for the NFTs to be sent to the receiver contract with SendNft,
+/// e.g. `{"callback":{"msg":"eyJsaXN0Ijp7fX0="}}`. Other memos are ignored.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Ics721Memo {
+    pub callback: Option<Ics721Callback>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Ics721Callback {
+    /// passed to the ReceiveNft hook of the receiver along with each NFT
+    pub msg: Binary,
+}
+
+/// The acknowledgement of an ICS-721 packet, in the standard ICS-4 format
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Ics721Ack {
+    Result(Binary),
+    Error(String),
 }
 
 impl Ics721Packet {
     pub fn new(
         class_id: &str,
         class_uri: Option<&str>,
+        class_data: Option<Binary>,
         token_ids: Vec<&str>,
         token_uris: Vec<&str>,
         sender: &str,
@@ -50,6 +96,7 @@ impl Ics721Packet {
         Ics721Packet {
             class_id: class_id.to_string(),
             class_uri: class_uri.map(str::to_string),
+            class_data,
             token_ids: token_ids
                 .iter()
                 .map(|&s| s.to_string())
@@ -58,61 +105,168 @@ impl Ics721Packet {
                 .iter()
                 .map(|&s| s.to_string())
                 .collect::<Vec<String>>(),
+            token_data: vec![],
             sender: sender.to_string(),
             receiver: receiver.to_string(),
+            memo: None,
         }
     }
 
+    /// The callback message requested in the memo, if any
+    pub fn callback(&self) -> Option<Binary> {
+        let memo: Ics721Memo = from_slice(self.memo.as_deref()?.as_bytes()).ok()?;
+        memo.callback.map(|callback| callback.msg)
+    }
+
     pub fn validate(&self) -> Result<(), ContractError> {
-        // TODO:
-        // https://github.com/public-awesome/contracts/issues/53
-        // validate class_id is a contract address
-        // validate class_uri is a uri if it exists
-        // validate same number of token_ids and token_uris
+        if !is_valid_id(&self.class_id, MAX_CLASS_ID_LENGTH) {
+            return Err(ContractError::InvalidClassId {
+                class_id: self.class_id.clone(),
+            });
+        }
+        if let Some(class_uri) = self.class_uri.as_deref().filter(|uri| !uri.is_empty()) {
+            validate_uri(class_uri)?;
+        }
+        if let Some(class_data) = &self.class_data {
+            if class_data.len() > MAX_CLASS_DATA_LENGTH {
+                return Err(ContractError::FieldTooLong {
+                    field: "class_data".to_string(),
+                    max: MAX_CLASS_DATA_LENGTH,
+                });
+            }
+        }
+
+        if let Some(memo) = &self.memo {
+            if memo.len() > MAX_MEMO_LENGTH {
+                return Err(ContractError::FieldTooLong {
+                    field: "memo".to_string(),
+                    max: MAX_MEMO_LENGTH,
+                });
+            }
+        }
+
+        if self.token_ids.is_empty() {
+            return Err(ContractError::NoTokenIds {});
+        }
+        // token uris and data are optional, but when given there is one per token
+        if !self.token_uris.is_empty() && self.token_ids.len() != self.token_uris.len() {
+            return Err(ContractError::TokenUrisMismatch {
+                token_ids: self.token_ids.len(),
+                token_uris: self.token_uris.len(),
+            });
+        }
+        if !self.token_data.is_empty() && self.token_ids.len() != self.token_data.len() {
+            return Err(ContractError::TokenDataMismatch {
+                token_ids: self.token_ids.len(),
+                token_data: self.token_data.len(),
+            });
+        }
+        let mut seen = BTreeSet::new();
+        for token_id in &self.token_ids {
+            if !is_valid_id(token_id, MAX_TOKEN_ID_LENGTH) {
+                return Err(ContractError::InvalidTokenId {
+                    token_id: token_id.clone(),
+                });
+            }
+            if !seen.insert(token_id) {
+                return Err(ContractError::DuplicateTokenId {
+                    token_id: token_id.clone(),
+                });
+            }
+        }
+        // tokens without metadata are sent with an empty uri
+        for token_uri in self.token_uris.iter().filter(|uri| !uri.is_empty()) {
+            validate_uri(token_uri)?;
+        }
         Ok(())
     }
 }
 
-// TODO: copy pasta, make parent public
-// create a serialized success message
+// size limits of packet fields, in bytes
+const MAX_CLASS_ID_LENGTH: usize = 512;
+const MAX_TOKEN_ID_LENGTH: usize = 256;
+const MAX_URI_LENGTH: usize = 2048;
+const MAX_CLASS_DATA_LENGTH: usize = 4096;
+const MAX_MEMO_LENGTH: usize = 4096;
+
+// ids can't be empty or contain whitespace or control characters
+fn is_valid_id(id: &str, max_length: usize) -> bool {
+    !id.is_empty()
+        && id.len() <= max_length
+        && !id.chars().any(|c| c.is_whitespace() || c.is_control())
+}
+
+fn validate_uri(uri: &str) -> Result<(), ContractError> {
+    if uri.len() > MAX_URI_LENGTH {
+        return Err(ContractError::FieldTooLong {
+            field: "uri".to_string(),
+            max: MAX_URI_LENGTH,
+        });
+    }
+    Url::parse(uri).map_err(|_| ContractError::InvalidUri {
+        uri: uri.to_string(),
+    })?;
+    Ok(())
+}
+
+// create a serialized success message, the single byte 0x01 like other implementations
 fn ack_success() -> Binary {
-    let res = Ics20Ack::Result(b"1".into());
+    let res = Ics721Ack::Result(Binary::from(vec![1]));
     to_binary(&res).unwrap()
 }
 
-// TODO: copy pasta, make parent public
 // create a serialized error message
 fn ack_fail(err: String) -> Binary {
-    let res = Ics20Ack::Error(err);
+    let res = Ics721Ack::Error(err);
     to_binary(&res).unwrap()
 }
 
 const SEND_NFT_ID: u64 = 1338;
+const INSTANTIATE_VOUCHER_ID: u64 = 1339;
+
+// placeholder collection image for vouchers of classes without a class_uri
+const DEFAULT_VOUCHER_IMAGE: &str = "https://www.stargaze.zone";
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(_deps: DepsMut, _env: Env, reply: Reply) -> Result<Response, ContractError> {
-    if reply.id != SEND_NFT_ID {
-        return Err(ContractError::UnknownReplyId { id: reply.id });
-    }
-    let res = match reply.result {
-        SubMsgResult::Ok(_) => Response::new(),
-        SubMsgResult::Err(err) => {
-            // encode an acknowledgement error
-            Response::new().set_data(ack_fail(err))
+pub fn reply(deps: DepsMut, _env: Env, reply: Reply) -> Result<Response, ContractError> {
+    match reply.id {
+        SEND_NFT_ID => Ok(match reply.result {
+            SubMsgResult::Ok(_) => Response::new(),
+            SubMsgResult::Err(err) => {
+                // encode an acknowledgement error
+                Response::new().set_data(ack_fail(err))
+            }
+        }),
+        INSTANTIATE_VOUCHER_ID => {
+            let class_id = PENDING_VOUCHER_CLASS_ID.load(deps.storage)?;
+            PENDING_VOUCHER_CLASS_ID.remove(deps.storage);
+            if let SubMsgResult::Err(err) = reply.result {
+                return Ok(Response::new().set_data(ack_fail(err)));
+            }
+
+            let res = parse_reply_instantiate_data(reply)?;
+            let contract_addr = deps.api.addr_validate(&res.contract_address)?;
+            CLASS_ID_TO_CONTRACT.save(deps.storage, &class_id, &contract_addr)?;
+            CONTRACT_TO_CLASS_ID.save(deps.storage, &contract_addr, &class_id)?;
+            Ok(Response::new()
+                .add_attribute("action", "instantiate_voucher")
+                .add_attribute("class_id", class_id)
+                .add_attribute("contract_addr", contract_addr))
         }
-    };
-    Ok(res)
+        id => Err(ContractError::UnknownReplyId { id }),
+    }
 }
 
 // IBC entrypoint 1
 #[cfg_attr(not(feature = "library"), entry_point)]
-/// enforces ordering and versioning constraints
+/// enforces ordering and versioning constraints, and the counterparty allowlist
 pub fn ibc_channel_open(
-    _deps: DepsMut,
+    deps: DepsMut,
     _env: Env,
     msg: IbcChannelOpenMsg,
 ) -> Result<(), ContractError> {
     enforce_order_and_version(msg.channel(), msg.counterparty_version())?;
+    enforce_counterparty_allowlist(deps.storage, msg.channel())?;
     Ok(())
 }
 
@@ -160,16 +314,42 @@ fn enforce_order_and_version(
     Ok(())
 }
 
+fn enforce_counterparty_allowlist(
+    storage: &dyn Storage,
+    channel: &IbcChannel,
+) -> Result<(), ContractError> {
+    let allowlist = match COUNTERPARTY_ALLOWLIST.may_load(storage)? {
+        Some(allowlist) => allowlist,
+        None => return Ok(()),
+    };
+    let allowed = allowlist.iter().any(|counterparty| {
+        counterparty.connection_id == channel.connection_id
+            && counterparty.port_id == channel.counterparty_endpoint.port_id
+    });
+    if !allowed {
+        return Err(ContractError::CounterpartyNotAllowed {
+            connection_id: channel.connection_id.clone(),
+            port_id: channel.counterparty_endpoint.port_id.clone(),
+        });
+    }
+    Ok(())
+}
+
 // IBC entrypoint 3
 #[cfg_attr(not(feature = "library"), entry_point)]
+/// mark the channel closed, NFTs escrowed on it can then be recovered by the admin
 pub fn ibc_channel_close(
-    _deps: DepsMut,
+    deps: DepsMut,
     _env: Env,
-    _channel: IbcChannelCloseMsg,
+    msg: IbcChannelCloseMsg,
 ) -> Result<IbcBasicResponse, ContractError> {
-    // TODO: what to do here?
-    // we will have locked funds that need to be returned somehow
-    unimplemented!();
+    let channel: IbcChannel = msg.into();
+    let channel_id = channel.endpoint.channel_id;
+    CLOSED_CHANNELS.save(deps.storage, &channel_id, &Empty {})?;
+
+    Ok(IbcBasicResponse::new()
+        .add_attribute("action", "channel_close")
+        .add_attribute("channel_id", channel_id))
 }
 
 // IBC entrypoint 4
@@ -177,31 +357,13 @@ pub fn ibc_channel_close(
 /// We should not return an error if possible, but rather an acknowledgement of failure
 pub fn ibc_packet_receive(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     msg: IbcPacketReceiveMsg,
 ) -> Result<IbcReceiveResponse, Never> {
     let packet = msg.packet;
 
-    let res = match do_ibc_packet_receive(deps, &packet) {
-        Ok(msg) => {
-            // this cannot fail as we did it before..
-            // TODO: find a way to not call this again?
-            let contract_addr = parse_voucher_contract_address(&msg.class_id, &packet.src).unwrap();
-
-            let attributes = vec![
-                attr("action", "receive"),
-                attr("sender", &msg.sender),
-                attr("receiver", &msg.receiver),
-                attr("contract_address", contract_addr.to_string()),
-                attr("token_ids", msg.token_ids.join(",")),
-                attr("success", "true"),
-            ];
-            let msg = send_tokens(contract_addr, msg.token_ids, msg.token_uris, msg.receiver);
-            IbcReceiveResponse::new()
-                .set_ack(ack_success())
-                .add_submessage(msg)
-                .add_attributes(attributes)
-        }
+    let res = match do_ibc_packet_receive(deps, &env, &packet) {
+        Ok(res) => res,
         Err(err) => IbcReceiveResponse::new()
             .set_ack(ack_fail(err.to_string()))
             .add_attributes(vec![
@@ -223,8 +385,6 @@ fn parse_voucher_contract_address<'a>(
 ) -> Result<&'a str, ContractError> {
     let split_class_id: Vec<&str> = voucher_class_id.splitn(3, '/').collect();
     if split_class_id.len() != 3 {
-        // only accept NFTs originating from this chain
-        // https://github.com/public-awesome/contracts/issues/56
         return Err(ContractError::NoForeignTokens {});
     }
     // a few more sanity checks
@@ -243,24 +403,47 @@ fn parse_voucher_contract_address<'a>(
 }
 
 // this does the work of ibc_packet_receive, we wrap it to turn errors into acknowledgements
-fn do_ibc_packet_receive(deps: DepsMut, packet: &IbcPacket) -> Result<Ics721Packet, ContractError> {
+fn do_ibc_packet_receive(
+    deps: DepsMut,
+    env: &Env,
+    packet: &IbcPacket,
+) -> Result<IbcReceiveResponse, ContractError> {
     let msg: Ics721Packet = from_binary(&packet.data)?;
-    let channel = packet.dest.channel_id.clone();
-
-    // If the token originated on another chain, it looks like "juno1.....".
-    // TODO: handle tokens originating from a remote chain
-    // https://github.com/public-awesome/contracts/issues/56
+    msg.validate()?;
+    if PAUSED_CHANNELS.has(deps.storage, &packet.dest.channel_id) {
+        return Err(ContractError::ChannelPaused {
+            id: packet.dest.channel_id.clone(),
+        });
+    }
 
-    // If it originated on our chain, it looks like "port/channel/stars1.....".
-    let contract_addr = parse_voucher_contract_address(&msg.class_id, &packet.src)?;
+    // If it originated on our chain, it looks like "port/channel/stars1.....",
+    // prefixed with the endpoint it was sent back from.
+    // Anything else originated on another chain and is received as a voucher.
+    match parse_voucher_contract_address(&msg.class_id, &packet.src) {
+        Ok(contract_addr) => {
+            let contract_addr = contract_addr.to_string();
+            receive_returning_nfts(deps, env, packet, msg, contract_addr)
+        }
+        Err(_) => receive_foreign_nfts(deps, env, packet, msg),
+    }
+}
 
-    // We received an NFT with a class_id that looks like "port/channel/stars1..."
-    // This means that it originated on this chain, so we have to check the channel
-    // state and make sure we have a record of sending it.
-    // If we find it, remove it from state and return Ok.
-    // If we don't find it, return Err.
+// We received an NFT with a class_id that looks like "port/channel/stars1..."
+// This means that it originated on this chain, so we have to check the channel
+// state and make sure we have a record of sending it.
+// If we find it, it is removed from state and returned to the receiver by ReleaseNfts,
+// which reverts together with the transfers if they fail.
+// If we don't find it, return Err.
+fn receive_returning_nfts(
+    deps: DepsMut,
+    env: &Env,
+    packet: &IbcPacket,
+    msg: Ics721Packet,
+    contract_addr: String,
+) -> Result<IbcReceiveResponse, ContractError> {
+    let channel = packet.dest.channel_id.clone();
     for token_id in &msg.token_ids {
-        let state = CHANNEL_STATE.may_load(deps.storage, (&channel, contract_addr, token_id))?;
+        let state = CHANNEL_STATE.may_load(deps.storage, (&channel, &contract_addr, token_id))?;
         match state {
             Some(_) => (),
             None => {
@@ -270,10 +453,146 @@ fn do_ibc_packet_receive(deps: DepsMut, packet: &IbcPacket) -> Result<Ics721Pack
             }
         };
     }
-    for token_id in &msg.token_ids {
-        CHANNEL_STATE.remove(deps.storage, (&channel, contract_addr, token_id));
+    // only counted by ReleaseNfts, so nothing is counted if the release fails
+    check_rate_limits(
+        deps.storage,
+        env.block.time,
+        &channel,
+        &contract_addr,
+        msg.token_ids.len() as u64,
+        false,
+    )?;
+
+    let attributes = vec![
+        attr("action", "receive"),
+        attr("sender", &msg.sender),
+        attr("receiver", &msg.receiver),
+        attr("contract_address", &contract_addr),
+        attr("token_ids", msg.token_ids.join(",")),
+        attr("success", "true"),
+    ];
+    let release = WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        msg: to_binary(&ExecuteMsg::ReleaseNfts {
+            channel_id: channel,
+            contract_addr,
+            token_ids: msg.token_ids.clone(),
+            receiver: msg.receiver.clone(),
+            callback: msg.callback(),
+        })?,
+        funds: vec![],
+    };
+    Ok(IbcReceiveResponse::new()
+        .set_ack(ack_success())
+        .add_submessage(SubMsg::reply_on_error(release, SEND_NFT_ID))
+        .add_attributes(attributes))
+}
+
+// We received an NFT that originated on another chain. It is represented here by a
+// voucher in a collection for the local class_id "port/channel/class_id".
+// The collection is instantiated the first time the class is seen, after which
+// this contract mints the vouchers to the receiver.
+fn receive_foreign_nfts(
+    deps: DepsMut,
+    env: &Env,
+    packet: &IbcPacket,
+    msg: Ics721Packet,
+) -> Result<IbcReceiveResponse, ContractError> {
+    let class_id = format!(
+        "{}/{}/{}",
+        packet.dest.port_id, packet.dest.channel_id, msg.class_id
+    );
+    // only counted by MintVouchers, so nothing is counted if the mints fail
+    check_rate_limits(
+        deps.storage,
+        env.block.time,
+        &packet.dest.channel_id,
+        &class_id,
+        msg.token_ids.len() as u64,
+        false,
+    )?;
+
+    let mut res = IbcReceiveResponse::new().set_ack(ack_success());
+    if !CLASS_ID_TO_CONTRACT.has(deps.storage, &class_id) {
+        res = res.add_submessage(instantiate_voucher(deps, env, &class_id, &msg)?);
     }
-    Ok(msg)
+
+    let mint = WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        msg: to_binary(&ExecuteMsg::MintVouchers {
+            channel_id: packet.dest.channel_id.clone(),
+            class_id: class_id.clone(),
+            token_ids: msg.token_ids.clone(),
+            token_uris: msg.token_uris.clone(),
+            receiver: msg.receiver.clone(),
+            callback: msg.callback(),
+        })?,
+        funds: vec![],
+    };
+
+    Ok(res
+        .add_submessage(SubMsg::reply_on_error(mint, SEND_NFT_ID))
+        .add_attributes(vec![
+            attr("action", "receive"),
+            attr("sender", &msg.sender),
+            attr("receiver", &msg.receiver),
+            attr("class_id", class_id),
+            attr("token_ids", msg.token_ids.join(",")),
+            attr("success", "true"),
+        ]))
+}
+
+fn instantiate_voucher(
+    deps: DepsMut,
+    env: &Env,
+    class_id: &str,
+    msg: &Ics721Packet,
+) -> Result<SubMsg, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    PENDING_VOUCHER_CLASS_ID.save(deps.storage, &class_id.to_string())?;
+
+    // describe the vouchers like the original collection if its info was sent along
+    let class_info: Option<CollectionInfoResponse> = msg
+        .class_data
+        .as_ref()
+        .and_then(|data| from_binary(data).ok());
+    let (description, image, external_link) = match class_info {
+        Some(info) => (info.description, info.image, info.external_link),
+        None => (
+            format!("ics721 vouchers for {}", class_id),
+            msg.class_uri
+                .clone()
+                .unwrap_or_else(|| DEFAULT_VOUCHER_IMAGE.to_string()),
+            None,
+        ),
+    };
+
+    let instantiate = WasmMsg::Instantiate {
+        admin: None,
+        code_id: config.voucher_code_id,
+        msg: to_binary(&Sg721InstantiateMsg {
+            name: class_id.to_string(),
+            symbol: "VOUCHER".to_string(),
+            minter: env.contract.address.to_string(),
+            provenance_hash: None,
+            collection_info: CollectionInfo {
+                creator: env.contract.address.to_string(),
+                description,
+                image,
+                external_link,
+                royalty_info: None,
+            },
+        })?,
+        funds: config.voucher_creation_fee.into_iter().collect(),
+        label: format!("ics721 voucher {}", class_id),
+    };
+    // a failed instantiation is turned into an error acknowledgement on reply
+    Ok(SubMsg {
+        id: INSTANTIATE_VOUCHER_ID,
+        msg: instantiate.into(),
+        gas_limit: None,
+        reply_on: ReplyOn::Always,
+    })
 }
 
 // IBC entrypoint 5
@@ -285,10 +604,10 @@ pub fn ibc_packet_ack(
     msg: IbcPacketAckMsg,
 ) -> Result<IbcBasicResponse, ContractError> {
     // TODO: trap error like in receive?
-    let ics20msg: Ics20Ack = from_binary(&msg.acknowledgement.data)?;
-    match ics20msg {
-        Ics20Ack::Result(_) => on_packet_success(deps, msg.original_packet),
-        Ics20Ack::Error(err) => on_packet_failure(deps, msg.original_packet, err),
+    let ack: Ics721Ack = from_binary(&msg.acknowledgement.data)?;
+    match ack {
+        Ics721Ack::Result(_) => on_packet_success(deps, msg.original_packet),
+        Ics721Ack::Error(err) => on_packet_failure(deps, msg.original_packet, err),
     }
 }
 
@@ -317,20 +636,75 @@ fn on_packet_success(deps: DepsMut, packet: IbcPacket) -> Result<IbcBasicRespons
     ];
 
     let channel = packet.src.channel_id;
+
+    // vouchers sent back to their origin chain are no longer backed, burn them
+    if let Some(contract_addr) = CLASS_ID_TO_CONTRACT.may_load(deps.storage, &msg.class_id)? {
+        for token in &msg.token_ids {
+            release_escrow(deps.storage, &channel, &msg.class_id, token);
+        }
+        return Ok(IbcBasicResponse::new()
+            .add_messages(burn_vouchers(&contract_addr, &msg.token_ids)?)
+            .add_attributes(attributes));
+    }
+
     for token in &msg.token_ids {
-        CHANNEL_STATE.save(deps.storage, (&channel, &msg.class_id, token), &Empty {})?;
+        add_channel_token(deps.storage, &channel, &msg.class_id, token)?;
     }
     Ok(IbcBasicResponse::new().add_attributes(attributes))
 }
 
-// return the tokens to sender
+fn burn_vouchers(contract_addr: &Addr, token_ids: &[String]) -> StdResult<Vec<WasmMsg>> {
+    token_ids
+        .iter()
+        .map(|token_id| {
+            Ok(WasmMsg::Execute {
+                contract_addr: contract_addr.to_string(),
+                msg: to_binary(&Sg721ExecuteMsg::Burn {
+                    token_id: token_id.clone(),
+                })?,
+                funds: vec![],
+            })
+        })
+        .collect()
+}
+
+// return the escrowed tokens to their sender
 fn on_packet_failure(
-    _deps: DepsMut,
+    deps: DepsMut,
     packet: IbcPacket,
     err: String,
 ) -> Result<IbcBasicResponse, ContractError> {
     let msg: Ics721Packet = from_binary(&packet.data)?;
-    let contract_addr = parse_voucher_contract_address(&msg.class_id, &packet.src)?;
+    // the class is either a voucher collection or the address of a local collection
+    let contract_addr = match CLASS_ID_TO_CONTRACT.may_load(deps.storage, &msg.class_id)? {
+        Some(contract_addr) => contract_addr.to_string(),
+        None => msg.class_id.clone(),
+    };
+
+    let channel = packet.src.channel_id;
+    let mut msgs = vec![];
+    for token_id in &msg.token_ids {
+        // tokens recovered by the admin after the channel closed are no longer escrowed,
+        // skipped so the acknowledgement or timeout can still be relayed
+        let sender = match ESCROWS.may_load(deps.storage, (&channel, &msg.class_id, token_id))? {
+            Some(sender) => sender,
+            None => continue,
+        };
+        // the token never left, so it doesn't count against the rate limits
+        if let Some(sent_at) =
+            ESCROW_SENT_AT.may_load(deps.storage, (&channel, &msg.class_id, token_id))?
+        {
+            refund_rate_limits(deps.storage, sent_at, &channel, &msg.class_id, 1)?;
+        }
+        release_escrow(deps.storage, &channel, &msg.class_id, token_id);
+        msgs.extend(send_tokens(
+            &contract_addr,
+            vec![token_id.clone()],
+            vec![],
+            sender.to_string(),
+        )?);
+    }
+
     let attributes = vec![
         attr("action", "acknowledge"),
         attr("sender", &msg.sender),
@@ -339,11 +713,9 @@ fn on_packet_failure(
         attr("success", "false"),
         attr("error", err),
     ];
-
-    let msg = send_tokens(contract_addr, msg.token_ids, msg.token_uris, msg.sender);
     Ok(IbcBasicResponse::new()
         .add_attributes(attributes)
-        .add_submessage(msg))
+        .add_submessages(msgs))
 }
 
 fn send_tokens(
@@ -351,20 +723,20 @@ fn send_tokens(
     token_ids: Vec<String>,
     _token_uris: Vec<String>,
     recipient: String,
-) -> SubMsg {
+) -> StdResult<Vec<SubMsg>> {
     // TODO: need a `TransferFullNft` or `TransferRemoteNft` that includes token_uri
-    let mut msgs: Vec<Cw721ExecuteMsg> = Vec::new();
-    for token_id in token_ids {
-        let msg = Cw721ExecuteMsg::TransferNft {
-            recipient: recipient.clone(),
-            token_id: token_id.clone(),
-        };
-        msgs.push(msg);
-    }
-    let exec = WasmMsg::Execute {
-        contract_addr: contract_addr.to_string(),
-        msg: to_binary(&msgs).unwrap(),
-        funds: vec![],
-    };
-    SubMsg::reply_on_error(exec, SEND_NFT_ID)
+    token_ids
+        .into_iter()
+        .map(|token_id| {
+            let exec = WasmMsg::Execute {
+                contract_addr: contract_addr.to_string(),
+                msg: to_binary(&Cw721ExecuteMsg::TransferNft {
+                    recipient: recipient.clone(),
+                    token_id,
+                })?,
+                funds: vec![],
+            };
+            Ok(SubMsg::reply_on_error(exec, SEND_NFT_ID))
+        })
+        .collect()
 }
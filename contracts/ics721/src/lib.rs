@@ -2,6 +2,7 @@ pub mod contract;
 mod error;
 pub mod ibc;
 pub mod msg;
+mod multitest;
 pub mod state;
 mod test_constants;
 mod test_helpers;
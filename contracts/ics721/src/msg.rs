@@ -1,42 +1,121 @@
-use cw20_ics20::state::ChannelInfo;
+use cosmwasm_std::{Binary, Coin, IbcTimeoutBlock};
 use cw721::Cw721ReceiveMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::state::{ChannelInfo, CollectionPolicy, Counterparty, RateLimit, RateLimitUsage};
+
 // use crate::state::ChannelState;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     // Default timeout for ics721 packets, specified in seconds
     pub default_timeout: u64,
+    // Longest timeout accepted for ics721 packets in seconds, unbounded if not set
+    pub max_timeout: Option<u64>,
+    // sg721 code used to instantiate voucher collections for NFTs from other chains
+    pub voucher_code_id: u64,
+    // Paid by this contract to instantiate a voucher collection, if required by the code
+    pub voucher_creation_fee: Option<Coin>,
+    // Can recover NFTs escrowed on closed channels, usually governance
+    pub admin: Option<String>,
 }
 
-// This is the message we accept via Receive
+// This is the message we accept via Receive.
+// The class is the sending cw721 contract and the token is the one it sent us.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct TransferMsg {
     // The local channel to send the packets on
     pub channel: String,
-    // uniquely identifies the collection to which the NFT belongs
-    pub class_id: String,
     // https://docs.opensea.io/docs/contract-level-metadata
     pub class_uri: Option<String>,
-    // uniquely identifies NFTs within the collection that is being transferred
-    pub token_ids: Vec<String>,
     // https://docs.opensea.io/docs/metadata-standards
-    pub token_uris: Vec<String>,
+    pub token_uri: Option<String>,
     // The remote address to send to
     pub remote_address: String,
-    // How long the packet lives in seconds. If not specified, use default_timeout.
+    // How long the packet lives in seconds. If not specified, use the channel's default timeout.
     pub timeout: Option<u64>,
+    // Height of the remote chain at which the packet also times out
+    pub timeout_block: Option<IbcTimeoutBlock>,
+    // Sent along in the packet, see Ics721Memo for asking the remote chain for a callback
+    pub memo: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
-    // Accepts an NFT from a cw721 contract, then transfers it over IBC
+    // Accepts an NFT sent by a cw721 contract with SendNft, escrows it,
+    // then transfers it over IBC to another chain
     Receive(Cw721ReceiveMsg),
-    // Transfer an NFT over IBC to another chain
-    Transfer(TransferMsg),
+    // Mints vouchers for NFTs received from another chain,
+    // sent to the receiver with SendNft and the callback msg if there is one.
+    // Can only be called by this contract while receiving a packet.
+    MintVouchers {
+        channel_id: String,
+        class_id: String,
+        token_ids: Vec<String>,
+        token_uris: Vec<String>,
+        receiver: String,
+        callback: Option<Binary>,
+    },
+    // Returns escrowed NFTs that came back from another chain to the receiver,
+    // with SendNft and the callback msg if there is one.
+    // Can only be called by this contract while receiving a packet.
+    ReleaseNfts {
+        channel_id: String,
+        contract_addr: String,
+        token_ids: Vec<String>,
+        receiver: String,
+        callback: Option<Binary>,
+    },
+    // Returns NFTs escrowed on a closed channel to the recipient. Admin only.
+    RecoverEscrow {
+        channel_id: String,
+        class_id: String,
+        token_ids: Vec<String>,
+        recipient: String,
+    },
+    // Sets the default timeout of packets sent on the channel in seconds,
+    // or goes back to the contract's default_timeout if not set. Admin only.
+    SetChannelTimeout {
+        channel_id: String,
+        default_timeout: Option<u64>,
+    },
+    // Pauses or unpauses sending and receiving NFTs on the channel. Admin only.
+    SetChannelPaused {
+        channel_id: String,
+        paused: bool,
+    },
+    // Only allows opening channels with these counterparties, or with any if not set.
+    // Existing channels are not affected. Admin only.
+    SetCounterpartyAllowlist {
+        counterparties: Option<Vec<Counterparty>>,
+    },
+    // Allows or blocks sending NFTs of the collection to other chains,
+    // or goes back to the default policy if not set. Admin only.
+    SetCollectionPolicy {
+        collection: String,
+        policy: Option<CollectionPolicy>,
+    },
+    // Sets the policy of collections without their own. Admin only.
+    SetDefaultCollectionPolicy {
+        policy: CollectionPolicy,
+    },
+    // Limits the tokens sent and received on the channel, or removes the limit. Admin only.
+    SetChannelRateLimit {
+        channel_id: String,
+        limit: Option<RateLimit>,
+    },
+    // Limits the tokens of the class sent and received on any channel, or removes the limit.
+    // Admin only.
+    SetClassRateLimit {
+        class_id: String,
+        limit: Option<RateLimit>,
+    },
+    // Change or clear the admin
+    UpdateAdmin {
+        admin: Option<String>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -51,21 +130,129 @@ pub enum QueryMsg {
     Channel {
         id: String,
     },
-    // Returns a list of tokens sent to a contract on the channel
+    // Returns a list of tokens of a class sent over the channel, ordered by token_id
     // Return type: TokensResponse.
     Tokens {
         channel_id: String,
         class_id: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    // Returns the classes with tokens sent over the channel, ordered by class_id
+    // Return type: ClassIdsResponse.
+    ClassIds {
+        channel_id: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    // Returns the channel an NFT held by this contract was sent over and who sent it,
+    // error if it is not escrowed.
+    // Return type: EscrowedByResponse.
+    EscrowedBy {
+        class_id: String,
+        token_id: String,
+    },
+    // Returns the class_id a voucher collection was instantiated for.
+    // Return type: ClassIdResponse.
+    ClassIdForContract {
+        contract: String,
+    },
+    // Returns the counterparty allowlist and default collection policy.
+    // Return type: PolicyResponse.
+    Policy {},
+    // Returns whether NFTs of the collection can be sent to other chains.
+    // Return type: CollectionPolicyResponse.
+    CollectionPolicy {
+        collection: String,
+    },
+    // Returns the collections with their own policy, ordered by address
+    // Return type: CollectionPoliciesResponse.
+    CollectionPolicies {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    // Returns the rate limit of the channel and the tokens counted against it in the current window.
+    // Return type: RateLimitResponse.
+    ChannelRateLimit {
+        channel_id: String,
+    },
+    // Returns the rate limit of the class and the tokens counted against it in the current window.
+    // Return type: RateLimitResponse.
+    ClassRateLimit {
+        class_id: String,
     },
+    // Return type: AdminResponse
+    Admin {},
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PortResponse {
+    pub port_id: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ListChannelsResponse {
+    pub channels: Vec<ChannelInfo>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct ChannelResponse {
     pub info: ChannelInfo,
+    pub closed: bool,
+    pub paused: bool,
+    // timeout in seconds of packets sent on the channel without one
+    pub default_timeout: u64,
+    // the first classes sent over the channel, use ClassIds to page through all of them
     pub class_ids: Vec<String>,
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PolicyResponse {
+    // channels can be opened with any counterparty if not set
+    pub counterparty_allowlist: Option<Vec<Counterparty>>,
+    pub default_collection_policy: CollectionPolicy,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct CollectionPolicyResponse {
+    pub policy: CollectionPolicy,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct CollectionPolicyInfo {
+    pub collection: String,
+    pub policy: CollectionPolicy,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct CollectionPoliciesResponse {
+    pub collections: Vec<CollectionPolicyInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RateLimitResponse {
+    pub limit: Option<RateLimit>,
+    pub usage: RateLimitUsage,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct TokensResponse {
     pub tokens: Vec<String>,
 }
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ClassIdsResponse {
+    pub class_ids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct EscrowedByResponse {
+    pub channel_id: String,
+    pub sender: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ClassIdResponse {
+    /// None if the contract is not a voucher collection
+    pub class_id: Option<String>,
+}
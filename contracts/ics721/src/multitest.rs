@@ -0,0 +1,297 @@
+#![cfg(test)]
+use cosmwasm_std::{Addr, DepsMut, Env, IbcChannelCloseMsg};
+use cw_multi_test::{Contract, ContractWrapper};
+use serde::{Deserialize, Serialize};
+use sg_multi_test::StargazeApp;
+use sg_std::StargazeMsgWrapper;
+
+use crate::contract::execute_transfer;
+use crate::error::ContractError;
+use crate::ibc::ibc_channel_close;
+use crate::msg::TransferMsg;
+use crate::test_constants::{CHANNEL_FROM_STARS_TO_OMNI, TEST_CHANNEL_0_DATA};
+use crate::test_helpers::{add_channel, mock_channel};
+
+type Response = cosmwasm_std::Response<StargazeMsgWrapper>;
+
+fn custom_mock_app() -> StargazeApp {
+    StargazeApp::default()
+}
+
+// IBC is not available in multitest, so the relayer is simulated through sudo
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum RelayerMsg {
+    OpenChannel {},
+    CloseChannel {},
+    // escrows an NFT the contract already owns, as Receive does before sending the packet
+    Transfer {
+        class_id: String,
+        token_id: String,
+        sender: String,
+    },
+}
+
+fn relayer(deps: DepsMut, env: Env, msg: RelayerMsg) -> Result<Response, ContractError> {
+    match msg {
+        RelayerMsg::OpenChannel {} => add_channel(deps, TEST_CHANNEL_0_DATA),
+        RelayerMsg::CloseChannel {} => {
+            let msg = IbcChannelCloseMsg::new_confirm(mock_channel(TEST_CHANNEL_0_DATA));
+            ibc_channel_close(deps, env, msg)?;
+        }
+        RelayerMsg::Transfer {
+            class_id,
+            token_id,
+            sender,
+        } => {
+            let msg = TransferMsg {
+                channel: CHANNEL_FROM_STARS_TO_OMNI.to_string(),
+                class_uri: None,
+                token_uri: None,
+                remote_address: "remote-rcpt".to_string(),
+                timeout: None,
+                timeout_block: None,
+                memo: None,
+            };
+            // the packet itself goes nowhere
+            execute_transfer(
+                deps,
+                env,
+                msg,
+                class_id,
+                None,
+                token_id,
+                Addr::unchecked(sender),
+            )?;
+        }
+    }
+    Ok(Response::new())
+}
+
+pub fn contract_ics721() -> Box<dyn Contract<StargazeMsgWrapper>> {
+    let contract = ContractWrapper::new_with_empty(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    )
+    .with_sudo(relayer);
+    Box::new(contract)
+}
+
+pub fn contract_sg721() -> Box<dyn Contract<StargazeMsgWrapper>> {
+    let contract = ContractWrapper::new(
+        sg721::contract::execute,
+        sg721::contract::instantiate,
+        sg721::contract::query,
+    );
+    Box::new(contract)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msg::{EscrowedByResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
+    use crate::test_helpers::{ADMIN_ADDR, DEFAULT_TIMEOUT, VOUCHER_CODE_ID};
+    use cosmwasm_std::{coins, to_binary, Empty, StdResult};
+    use cw721::{Cw721QueryMsg, OwnerOfResponse};
+    use cw721_base::MintMsg;
+    use cw_controllers::AdminError;
+    use cw_multi_test::{BankSudo, Executor, SudoMsg};
+    use sg721::msg::{ExecuteMsg as Sg721ExecuteMsg, InstantiateMsg as Sg721InstantiateMsg};
+    use sg721::state::CollectionInfo;
+    use sg_std::NATIVE_DENOM;
+
+    const CREATION_FEE: u128 = 1_000_000_000;
+
+    // Instantiates an sg721 collection and ics721 with a channel open
+    fn setup_contracts(router: &mut StargazeApp, creator: &Addr) -> (Addr, Addr) {
+        router
+            .sudo(SudoMsg::Bank(BankSudo::Mint {
+                to_address: creator.to_string(),
+                amount: coins(CREATION_FEE, NATIVE_DENOM),
+            }))
+            .unwrap();
+
+        let sg721_id = router.store_code(contract_sg721());
+        let msg = Sg721InstantiateMsg {
+            name: String::from("Test Coin"),
+            symbol: String::from("TEST"),
+            minter: creator.to_string(),
+            provenance_hash: None,
+            collection_info: CollectionInfo {
+                creator: creator.to_string(),
+                description: String::from("Stargaze Monkeys"),
+                image: "https://example.com/image.png".to_string(),
+                external_link: None,
+                royalty_info: None,
+            },
+        };
+        let collection = router
+            .instantiate_contract(
+                sg721_id,
+                creator.clone(),
+                &msg,
+                &coins(CREATION_FEE, NATIVE_DENOM),
+                "NFT",
+                None,
+            )
+            .unwrap();
+
+        let ics721_id = router.store_code(contract_ics721());
+        let msg = InstantiateMsg {
+            default_timeout: DEFAULT_TIMEOUT,
+            max_timeout: None,
+            voucher_code_id: VOUCHER_CODE_ID,
+            voucher_creation_fee: None,
+            admin: Some(ADMIN_ADDR.to_string()),
+        };
+        let ics721 = router
+            .instantiate_contract(ics721_id, creator.clone(), &msg, &[], "ics721", None)
+            .unwrap();
+        router
+            .wasm_sudo(ics721.clone(), &RelayerMsg::OpenChannel {})
+            .unwrap();
+
+        (collection, ics721)
+    }
+
+    fn mint(
+        router: &mut StargazeApp,
+        creator: &Addr,
+        collection: &Addr,
+        token_id: &str,
+        owner: &Addr,
+    ) {
+        let msg = Sg721ExecuteMsg::Mint(MintMsg {
+            token_id: token_id.to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: Empty {},
+        });
+        router
+            .execute_contract(creator.clone(), collection.clone(), &msg, &[])
+            .unwrap();
+    }
+
+    fn owner_of(router: &StargazeApp, collection: &Addr, token_id: &str) -> String {
+        let res: OwnerOfResponse = router
+            .wrap()
+            .query_wasm_smart(
+                collection,
+                &Cw721QueryMsg::OwnerOf {
+                    token_id: token_id.to_string(),
+                    include_expired: None,
+                },
+            )
+            .unwrap();
+        res.owner
+    }
+
+    #[test]
+    fn recover_escrow_after_channel_close() {
+        let mut router = custom_mock_app();
+        let creator = Addr::unchecked("creator");
+        let owner = Addr::unchecked("owner");
+        let admin = Addr::unchecked(ADMIN_ADDR);
+        let (collection, ics721) = setup_contracts(&mut router, &creator);
+
+        // two NFTs are sent over the channel and held by ics721
+        for token_id in ["1", "2"] {
+            mint(&mut router, &creator, &collection, token_id, &ics721);
+            router
+                .wasm_sudo(
+                    ics721.clone(),
+                    &RelayerMsg::Transfer {
+                        class_id: collection.to_string(),
+                        token_id: token_id.to_string(),
+                        sender: owner.to_string(),
+                    },
+                )
+                .unwrap();
+        }
+        let recover_msg = ExecuteMsg::RecoverEscrow {
+            channel_id: CHANNEL_FROM_STARS_TO_OMNI.to_string(),
+            class_id: collection.to_string(),
+            token_ids: vec!["1".to_string(), "2".to_string()],
+            recipient: owner.to_string(),
+        };
+
+        // escrows can't be recovered while the NFTs can still come back over the channel
+        let err = router
+            .execute_contract(admin.clone(), ics721.clone(), &recover_msg, &[])
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::ChannelOpen {
+                id: CHANNEL_FROM_STARS_TO_OMNI.to_string()
+            }
+            .to_string()
+        );
+
+        router
+            .wasm_sudo(ics721.clone(), &RelayerMsg::CloseChannel {})
+            .unwrap();
+
+        // new transfers on the closed channel are rejected
+        mint(&mut router, &creator, &collection, "3", &owner);
+        let transfer_msg = TransferMsg {
+            channel: CHANNEL_FROM_STARS_TO_OMNI.to_string(),
+            class_uri: None,
+            token_uri: None,
+            remote_address: "remote-rcpt".to_string(),
+            timeout: None,
+            timeout_block: None,
+            memo: None,
+        };
+        let send_msg = Sg721ExecuteMsg::SendNft {
+            contract: ics721.to_string(),
+            token_id: "3".to_string(),
+            msg: to_binary(&transfer_msg).unwrap(),
+        };
+        let err = router
+            .execute_contract(owner.clone(), collection.clone(), &send_msg, &[])
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::ChannelClosed {
+                id: CHANNEL_FROM_STARS_TO_OMNI.to_string()
+            }
+            .to_string()
+        );
+        assert_eq!(owner_of(&router, &collection, "3"), owner.to_string());
+
+        // only the admin can recover
+        let err = router
+            .execute_contract(owner.clone(), ics721.clone(), &recover_msg, &[])
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            AdminError::NotAdmin {}.to_string()
+        );
+
+        let res = router
+            .execute_contract(admin.clone(), ics721.clone(), &recover_msg, &[])
+            .unwrap();
+        let recover_event = res.events.iter().find(|event| event.ty == "wasm").unwrap();
+        assert!(recover_event
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "action" && attr.value == "recover_escrow"));
+        assert_eq!(owner_of(&router, &collection, "1"), owner.to_string());
+        assert_eq!(owner_of(&router, &collection, "2"), owner.to_string());
+
+        // the escrow records are gone, so the NFTs can't be recovered twice
+        let res: StdResult<EscrowedByResponse> = router.wrap().query_wasm_smart(
+            ics721.clone(),
+            &QueryMsg::EscrowedBy {
+                class_id: collection.to_string(),
+                token_id: "1".to_string(),
+            },
+        );
+        assert!(res.is_err());
+        let err = router
+            .execute_contract(admin, ics721, &recover_msg, &[])
+            .unwrap_err();
+        assert_eq!(err.root_cause().to_string(), "NoSuchNft");
+    }
+}
@@ -1,19 +1,179 @@
-use cosmwasm_std::Empty;
+use cosmwasm_std::{Addr, Coin, Empty, IbcEndpoint, StdResult, Storage, Timestamp};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cw20_ics20::state::ChannelInfo;
+use cw_controllers::Admin;
 use cw_storage_plus::{Item, Map};
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
 pub struct Config {
     pub default_timeout: u64,
+    pub max_timeout: Option<u64>,
+    /// sg721 code used to instantiate voucher collections for foreign classes
+    pub voucher_code_id: u64,
+    /// Paid by this contract to instantiate a voucher collection
+    pub voucher_creation_fee: Option<Coin>,
 }
 pub const CONFIG: Item<Config> = Item::new("ics721_config");
 
+/// Can recover NFTs escrowed on closed channels
+pub const ADMIN: Admin = Admin::new("admin");
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ChannelInfo {
+    /// id of this channel
+    pub id: String,
+    /// the remote channel/port we connect to
+    pub counterparty_endpoint: IbcEndpoint,
+    /// the connection this exists on (you can use to query client/consensus info)
+    pub connection_id: String,
+}
+
 /// static info on one channel that doesn't change
 pub const CHANNEL_INFO: Map<&str, ChannelInfo> = Map::new("channel_info");
 
+/// default timeout in seconds of packets sent on the channel, overrides Config.default_timeout
+pub const CHANNEL_TIMEOUTS: Map<&str, u64> = Map::new("channel_timeouts");
+
+/// channels that have been closed, they stay in CHANNEL_INFO so escrows can be recovered
+pub const CLOSED_CHANNELS: Map<&str, Empty> = Map::new("closed_channels");
+
+/// channels on which nothing can be sent or received until they are unpaused
+pub const PAUSED_CHANNELS: Map<&str, Empty> = Map::new("paused_channels");
+
+/// A chain, by the connection to it, and port that channels can be opened with
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Counterparty {
+    pub connection_id: String,
+    pub port_id: String,
+}
+
+/// If set, channels can only be opened with these counterparties
+pub const COUNTERPARTY_ALLOWLIST: Item<Vec<Counterparty>> = Item::new("counterparty_allowlist");
+
+/// Whether NFTs of a collection on this chain can be sent to other chains
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum CollectionPolicy {
+    Allowed,
+    Blocked,
+}
+
+/// Policy of the collections without one in COLLECTION_POLICIES, Allowed if not set
+pub const DEFAULT_COLLECTION_POLICY: Item<CollectionPolicy> =
+    Item::new("default_collection_policy");
+pub const COLLECTION_POLICIES: Map<&Addr, CollectionPolicy> = Map::new("collection_policies");
+
+/// At most max_tokens can be sent, and as many received, in each window of period seconds
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RateLimit {
+    pub max_tokens: u64,
+    pub period: u64,
+}
+
+/// Tokens sent and received in the window starting at window_start, in seconds
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct RateLimitUsage {
+    pub window_start: u64,
+    pub sent: u64,
+    pub received: u64,
+}
+
+/// Rate limits of the tokens crossing a channel, and of a class (by local class_id) on any channel
+pub const CHANNEL_RATE_LIMITS: Map<&str, RateLimit> = Map::new("channel_rate_limits");
+pub const CLASS_RATE_LIMITS: Map<&str, RateLimit> = Map::new("class_rate_limits");
+pub const CHANNEL_RATE_USAGE: Map<&str, RateLimitUsage> = Map::new("channel_rate_usage");
+pub const CLASS_RATE_USAGE: Map<&str, RateLimitUsage> = Map::new("class_rate_usage");
+
 /// Indexed by (channel_id, contract_addr, token_id)
 /// Keeps track of all NFTs that have passed through this channel.
 pub const CHANNEL_STATE: Map<(&str, &str, &str), Empty> = Map::new("channel_state");
+
+/// Indexed by (channel_id, class_id)
+/// Number of tokens of the class in CHANNEL_STATE, so classes can be listed without
+/// iterating over every token.
+pub const CHANNEL_CLASSES: Map<(&str, &str), u64> = Map::new("channel_classes");
+
+/// Indexed by (channel_id, class_id, token_id)
+/// NFTs held by this contract since being sent over the channel, with the address to
+/// return them to if the transfer fails.
+pub const ESCROWS: Map<(&str, &str, &str), Addr> = Map::new("escrows");
+
+/// Indexed by (class_id, token_id)
+/// The channel an escrowed token was sent over. This contract can only hold a token once.
+pub const ESCROW_CHANNELS: Map<(&str, &str), String> = Map::new("escrow_channels");
+
+/// Indexed by (channel_id, class_id, token_id)
+/// When an escrowed token was sent, so a failed transfer is refunded to the rate limit
+/// window it was counted in.
+pub const ESCROW_SENT_AT: Map<(&str, &str, &str), Timestamp> = Map::new("escrow_sent_at");
+
+/// Voucher collections minted for foreign classes, by local class_id (port/channel/class_id)
+pub const CLASS_ID_TO_CONTRACT: Map<&str, Addr> = Map::new("class_id_to_contract");
+pub const CONTRACT_TO_CLASS_ID: Map<&Addr, String> = Map::new("contract_to_class_id");
+
+/// The class_id of the voucher collection being instantiated, read back on reply
+pub const PENDING_VOUCHER_CLASS_ID: Item<String> = Item::new("pending_voucher_class_id");
+
+pub fn collection_policy(storage: &dyn Storage, collection: &Addr) -> StdResult<CollectionPolicy> {
+    match COLLECTION_POLICIES.may_load(storage, collection)? {
+        Some(policy) => Ok(policy),
+        None => Ok(DEFAULT_COLLECTION_POLICY
+            .may_load(storage)?
+            .unwrap_or(CollectionPolicy::Allowed)),
+    }
+}
+
+pub fn add_channel_token(
+    storage: &mut dyn Storage,
+    channel_id: &str,
+    class_id: &str,
+    token_id: &str,
+) -> StdResult<()> {
+    if CHANNEL_STATE.has(storage, (channel_id, class_id, token_id)) {
+        return Ok(());
+    }
+    CHANNEL_STATE.save(storage, (channel_id, class_id, token_id), &Empty {})?;
+    CHANNEL_CLASSES.update(storage, (channel_id, class_id), |count| -> StdResult<_> {
+        Ok(count.unwrap_or_default() + 1)
+    })?;
+    Ok(())
+}
+
+pub fn remove_channel_token(
+    storage: &mut dyn Storage,
+    channel_id: &str,
+    class_id: &str,
+    token_id: &str,
+) -> StdResult<()> {
+    if !CHANNEL_STATE.has(storage, (channel_id, class_id, token_id)) {
+        return Ok(());
+    }
+    CHANNEL_STATE.remove(storage, (channel_id, class_id, token_id));
+    let count = CHANNEL_CLASSES.load(storage, (channel_id, class_id))? - 1;
+    if count == 0 {
+        CHANNEL_CLASSES.remove(storage, (channel_id, class_id));
+    } else {
+        CHANNEL_CLASSES.save(storage, (channel_id, class_id), &count)?;
+    }
+    Ok(())
+}
+
+pub fn escrow_token(
+    storage: &mut dyn Storage,
+    channel_id: &str,
+    class_id: &str,
+    token_id: &str,
+    sender: &Addr,
+    sent_at: Timestamp,
+) -> StdResult<()> {
+    ESCROWS.save(storage, (channel_id, class_id, token_id), sender)?;
+    ESCROW_SENT_AT.save(storage, (channel_id, class_id, token_id), &sent_at)?;
+    ESCROW_CHANNELS.save(storage, (class_id, token_id), &channel_id.to_string())
+}
+
+pub fn release_escrow(storage: &mut dyn Storage, channel_id: &str, class_id: &str, token_id: &str) {
+    ESCROWS.remove(storage, (channel_id, class_id, token_id));
+    ESCROW_SENT_AT.remove(storage, (channel_id, class_id, token_id));
+    ESCROW_CHANNELS.remove(storage, (class_id, token_id));
+}
@@ -5,20 +5,25 @@
 use crate::contract::instantiate;
 use crate::ibc::{ibc_channel_connect, ibc_channel_open};
 use crate::ibc::{ICS721_ORDERING, ICS721_VERSION};
-use cw20_ics20::state::ChannelInfo;
 
 use cosmwasm_std::testing::{
     mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
 };
 use cosmwasm_std::{
-    DepsMut, IbcChannel, IbcChannelConnectMsg, IbcChannelOpenMsg, IbcEndpoint, OwnedDeps,
+    from_binary, to_binary, ContractResult, DepsMut, Empty, IbcChannel, IbcChannelConnectMsg,
+    IbcChannelOpenMsg, IbcEndpoint, OwnedDeps, SystemError, SystemResult, WasmQuery,
 };
+use cw721::{NftInfoResponse, OwnerOfResponse};
+use sg721::msg::{CollectionInfoResponse, QueryMsg as Sg721QueryMsg};
 
 use crate::msg::InstantiateMsg;
+use crate::state::ChannelInfo;
 
 pub const DEFAULT_TIMEOUT: u64 = 3600; // 1 hour,
+pub const VOUCHER_CODE_ID: u64 = 7;
 pub const CONTRACT_PORT: &str = "ibc:wasm1234567890abcdef";
 pub const REMOTE_PORT: &str = "transfer-nft";
+pub const ADMIN_ADDR: &str = "admin";
 
 pub struct ChannelSetupData<'a> {
     pub source_channel: &'a str,
@@ -70,6 +75,10 @@ pub fn setup(
     // instantiate an empty contract
     let instantiate_msg = InstantiateMsg {
         default_timeout: DEFAULT_TIMEOUT,
+        max_timeout: None,
+        voucher_code_id: VOUCHER_CODE_ID,
+        voucher_creation_fee: None,
+        admin: Some(ADMIN_ADDR.to_string()),
     };
     let info = mock_info(&String::from("anyone"), &[]);
     let res = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
@@ -87,3 +96,45 @@ pub fn setup(
     }
     deps
 }
+
+pub const TOKEN_URI: &str = "https://metadata-url.com/token";
+
+pub fn mock_collection_info() -> CollectionInfoResponse {
+    CollectionInfoResponse {
+        creator: "creator".to_string(),
+        description: "Stargaze Monkeys".to_string(),
+        image: "https://example.com/image.png".to_string(),
+        external_link: Some("https://example.com/collection".to_string()),
+        royalty_info: None,
+    }
+}
+
+// every sg721 contract reports `owner` as the owner of any token,
+// TOKEN_URI as its metadata and mock_collection_info() as the collection
+pub fn mock_collection(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, owner: &str) {
+    let owner = owner.to_string();
+    deps.querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { msg, .. } => {
+            let res = match from_binary(msg).unwrap() {
+                Sg721QueryMsg::OwnerOf { .. } => to_binary(&OwnerOfResponse {
+                    owner: owner.clone(),
+                    approvals: vec![],
+                }),
+                Sg721QueryMsg::NftInfo { .. } => to_binary(&NftInfoResponse {
+                    token_uri: Some(TOKEN_URI.to_string()),
+                    extension: Empty {},
+                }),
+                Sg721QueryMsg::CollectionInfo {} => to_binary(&mock_collection_info()),
+                _ => {
+                    return SystemResult::Err(SystemError::UnsupportedRequest {
+                        kind: "sg721".to_string(),
+                    })
+                }
+            };
+            SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+        }
+        _ => SystemResult::Err(SystemError::UnsupportedRequest {
+            kind: "wasm".to_string(),
+        }),
+    });
+}
@@ -1,16 +1,25 @@
-use cosmwasm_std::{Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Order, StdResult, to_binary, WasmMsg};
+use cosmwasm_std::{Addr, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Order, StdResult, to_binary, WasmMsg};
+use cw_storage_plus::Bound;
+use cw_utils::{maybe_addr, Expiration};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cw2::set_contract_version;
 use sg_std::StargazeMsgWrapper;
+use sha2::{Digest, Sha256};
 
-use sg721_imago::msg::ExecuteMsg as Imago721ExecuteMsg;
+use sg721_imago::msg::{
+    CollectionInfoResponse, ExecuteMsg as Imago721ExecuteMsg, QueryMsg as Imago721QueryMsg,
+};
 
 use crate::error::ContractError;
 use crate::msg::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
+    CollectionsResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, NonceResponse, OwnersResponse,
+    ProposalAction, ProposalResponse, ProposalsResponse, QueryMsg, SignersResponse,
+};
+use crate::state::{
+    BALLOTS, COLLECTION_SIGNER_PUBKEYS, COLLECTION_SIGNERS, COLLECTIONS, NONCES, OWNER, OWNERS, PENDING_OWNER, PendingOwner,
+    Proposal, PROPOSAL_COUNT, PROPOSALS, SIGNER_COLLECTIONS, SIGNER_PUBKEYS, SIGNERS, THRESHOLD,
 };
-use crate::state::{OWNER, SIGNERS};
 
 pub type Response = cosmwasm_std::Response<StargazeMsgWrapper>;
 pub type SubMsg = cosmwasm_std::SubMsg<StargazeMsgWrapper>;
@@ -19,6 +28,20 @@ pub type SubMsg = cosmwasm_std::SubMsg<StargazeMsgWrapper>;
 const CONTRACT_NAME: &str = "crates.io:imago-finalizer";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// domain separator for signed finalization payloads
+const FINALIZE_DOMAIN: &str = "imago-finalizer/finalize/v1";
+
+// most tokens finalized by one FinalizeBatch, bounds the gas of a single message
+const MAX_BATCH_SIZE: u32 = 50;
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+// ownership transfers and proposals expire after a week unless given an expiration
+const DEFAULT_EXPIRY_SECONDS: u64 = 7 * 24 * 60 * 60;
+const MAX_OWNERS: u32 = 20;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -48,14 +71,30 @@ pub fn instantiate(
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
-    _: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::TransferOwnership { to } => execute_transfer_ownership(deps, info, to),
+        ExecuteMsg::ProposeOwnership { to, expires } => execute_propose_ownership(deps, env, info, to, expires),
+        ExecuteMsg::AcceptOwnership {} => execute_accept_ownership(deps, env, info),
+        ExecuteMsg::CancelOwnership {} => execute_cancel_ownership(deps, info),
+        ExecuteMsg::UpdateOwners { owners, threshold } => execute_update_owners(deps, info, owners, threshold),
+        ExecuteMsg::Propose { action, expires } => execute_propose(deps, env, info, action, expires),
+        ExecuteMsg::Vote { proposal_id } => execute_vote(deps, env, info, proposal_id),
+        ExecuteMsg::Execute { proposal_id } => execute_execute_proposal(deps, env, info, proposal_id),
         ExecuteMsg::ChangeSigner { to, enabled } => execute_change_signer(deps, info, to, enabled),
+        ExecuteMsg::RegisterCollection { contract, signers } => execute_register_collection(deps, info, contract, signers),
+        ExecuteMsg::DeregisterCollection { contract } => execute_deregister_collection(deps, info, contract),
+        ExecuteMsg::ChangeCollectionSigner { contract, signer, enabled } =>
+            execute_change_collection_signer(deps, info, contract, signer, enabled),
         ExecuteMsg::Finalize { contract, token_uri, token_id } => execute_finalize(deps, info, contract, token_id, token_uri),
+        ExecuteMsg::FinalizeBatch { contract, items } => execute_finalize_batch(deps, info, contract, items),
+        ExecuteMsg::ChangeSignerPubkey { pubkey, enabled } => execute_change_signer_pubkey(deps, info, pubkey, enabled),
+        ExecuteMsg::ChangeCollectionSignerPubkey { contract, pubkey, enabled } =>
+            execute_change_collection_signer_pubkey(deps, info, contract, pubkey, enabled),
+        ExecuteMsg::FinalizeWithSignature { contract, token_id, token_uri, pubkey, signature } =>
+            execute_finalize_with_signature(deps, env, contract, token_id, token_uri, pubkey, signature),
     }
 }
 
@@ -64,65 +103,638 @@ pub fn execute_finalize(
     info: MessageInfo,
     contract: String, token_id: String, token_uri: String,
 ) -> Result<Response, ContractError> {
-    let valid_signer = SIGNERS.load(deps.storage, info.sender)?;
-    if !valid_signer {
+    let contract_addr = assert_collection_signer(deps.as_ref(), &info, &contract)?;
+    let finalize_msg = Imago721ExecuteMsg::FinalizeTokenUri {
+        token_id: token_id.to_string(),
+        token_uri: token_uri.to_string(),
+    };
+    let msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: contract_addr.to_string(),
+        msg: to_binary(&finalize_msg)?,
+        funds: vec![],
+    });
+
+    Ok(Response::default()
+        .add_attribute("action", "finalize")
+        .add_attribute("finalize_contract", contract)
+        .add_attribute("finalize_token_id", token_id)
+        .add_attribute("finalize_token_uri", token_uri)
+        .add_message(msg))
+}
+
+pub fn execute_finalize_batch(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract: String, items: Vec<(String, String)>,
+) -> Result<Response, ContractError> {
+    let contract_addr = assert_collection_signer(deps.as_ref(), &info, &contract)?;
+    if items.is_empty() {
+        return Err(ContractError::EmptyBatch {});
+    }
+    if items.len() > MAX_BATCH_SIZE as usize {
+        return Err(ContractError::BatchTooLarge { max: MAX_BATCH_SIZE });
+    }
+
+    let count = items.len();
+    let finalize_msg = Imago721ExecuteMsg::FinalizeTokenUris { items };
+    let msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: contract_addr.to_string(),
+        msg: to_binary(&finalize_msg)?,
+        funds: vec![],
+    });
+
+    Ok(Response::default()
+        .add_attribute("action", "finalize_batch")
+        .add_attribute("finalize_contract", contract)
+        .add_attribute("finalize_count", count.to_string())
+        .add_message(msg))
+}
+
+pub fn execute_finalize_with_signature(
+    deps: DepsMut,
+    env: Env,
+    contract: String, token_id: String, token_uri: String, pubkey: Binary, signature: Binary,
+) -> Result<Response, ContractError> {
+    let contract_addr = assert_registered(deps.as_ref(), &contract)?;
+    let nonce = NONCES.may_load(deps.storage, (&contract_addr, &token_id))?.unwrap_or(0);
+
+    // any relayer may submit, the signature must come from the given enabled public key
+    // scoped to the collection
+    if !SIGNER_PUBKEYS.may_load(deps.storage, pubkey.as_slice())?.unwrap_or(false) {
         return Err(ContractError::Unauthorized(
-            "Sender is not an signer".to_owned(),
+            "Public key is not an enabled signer".to_owned(),
         ));
-    };
+    }
+    if !COLLECTION_SIGNER_PUBKEYS.has(deps.storage, (&contract_addr, pubkey.as_slice())) {
+        return Err(ContractError::Unauthorized(
+            "Public key is not a signer for this collection".to_owned(),
+        ));
+    }
+    let payload = finalize_payload(&env.block.chain_id, &contract_addr, &token_id, &token_uri, nonce);
+    let hash = Sha256::digest(&payload);
+    if !deps.api.secp256k1_verify(&hash, &signature, &pubkey).unwrap_or(false) {
+        return Err(ContractError::InvalidSignature {});
+    }
+
+    // a signature can only be used once
+    NONCES.save(deps.storage, (&contract_addr, &token_id), &(nonce + 1))?;
+
     let finalize_msg = Imago721ExecuteMsg::FinalizeTokenUri {
         token_id: token_id.to_string(),
         token_uri: token_uri.to_string(),
     };
     let msg = CosmosMsg::Wasm(WasmMsg::Execute {
-        contract_addr: contract.to_string(),
+        contract_addr: contract_addr.to_string(),
         msg: to_binary(&finalize_msg)?,
         funds: vec![],
     });
 
     Ok(Response::default()
-        .add_attribute("action", "finalize")
+        .add_attribute("action", "finalize_with_signature")
         .add_attribute("finalize_contract", contract)
         .add_attribute("finalize_token_id", token_id)
         .add_attribute("finalize_token_uri", token_uri)
+        .add_attribute("finalize_nonce", nonce.to_string())
         .add_message(msg))
 }
 
+/// Bytes a signer hashes with sha256 and signs to authorize `FinalizeWithSignature`.
+/// Every field is length prefixed so no two payloads share an encoding.
+pub fn finalize_payload(
+    chain_id: &str,
+    contract: &Addr,
+    token_id: &str,
+    token_uri: &str,
+    nonce: u64,
+) -> Vec<u8> {
+    let mut payload: Vec<u8> = vec![];
+    for field in [FINALIZE_DOMAIN, chain_id, contract.as_str(), token_id, token_uri] {
+        payload.extend_from_slice(&(field.len() as u32).to_be_bytes());
+        payload.extend_from_slice(field.as_bytes());
+    }
+    payload.extend_from_slice(&nonce.to_be_bytes());
+    payload
+}
 
-pub fn execute_transfer_ownership(
+pub fn execute_change_signer_pubkey(
     deps: DepsMut,
     info: MessageInfo,
-    to: String,
+    pubkey: Binary,
+    enabled: bool,
 ) -> Result<Response, ContractError> {
+    assert_owner(deps.as_ref(), &info)?;
+    assert_single_owner(deps.as_ref())?;
+
+    change_signer_pubkey(deps, pubkey, enabled)
+}
+
+fn change_signer_pubkey(
+    deps: DepsMut,
+    pubkey: Binary,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    validate_pubkey(&pubkey)?;
+    SIGNER_PUBKEYS.save(deps.storage, pubkey.as_slice(), &enabled)?;
+
+    Ok(Response::default()
+        .add_attribute("action", "finalize_change_signer_pubkey")
+        .add_attribute("finalize_change_signer_pubkey", pubkey.to_base64())
+        .add_attribute("finalize_change_signer_enabled", &enabled.to_string())
+    )
+}
+
+pub fn execute_change_collection_signer_pubkey(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract: String,
+    pubkey: Binary,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.as_ref(), &info)?;
+    assert_single_owner(deps.as_ref())?;
+
+    change_collection_signer_pubkey(deps, contract, pubkey, enabled)
+}
+
+fn change_collection_signer_pubkey(
+    deps: DepsMut,
+    contract: String,
+    pubkey: Binary,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    let contract_addr = assert_registered(deps.as_ref(), &contract)?;
+    validate_pubkey(&pubkey)?;
+    if enabled {
+        COLLECTION_SIGNER_PUBKEYS.save(deps.storage, (&contract_addr, pubkey.as_slice()), &true)?;
+    } else {
+        COLLECTION_SIGNER_PUBKEYS.remove(deps.storage, (&contract_addr, pubkey.as_slice()));
+    }
+
+    Ok(Response::default()
+        .add_attribute("action", "finalize_change_collection_signer_pubkey")
+        .add_attribute("finalize_collection", &contract)
+        .add_attribute("finalize_change_signer_pubkey", pubkey.to_base64())
+        .add_attribute("finalize_change_signer_enabled", &enabled.to_string())
+    )
+}
+
+// secp256k1 public keys are 33 bytes compressed or 65 bytes uncompressed
+fn validate_pubkey(pubkey: &Binary) -> Result<(), ContractError> {
+    if pubkey.len() != 33 && pubkey.len() != 65 {
+        return Err(ContractError::InvalidPubkey {});
+    }
+    Ok(())
+}
+
+// Sender must be an enabled signer scoped to the registered collection
+fn assert_collection_signer(deps: Deps, info: &MessageInfo, contract: &str) -> Result<Addr, ContractError> {
+    let valid_signer = SIGNERS.may_load(deps.storage, info.sender.clone())?.unwrap_or(false);
+    if !valid_signer {
+        return Err(ContractError::Unauthorized(
+            "Sender is not an signer".to_owned(),
+        ));
+    };
+    let contract_addr = assert_registered(deps, contract)?;
+    if !COLLECTION_SIGNERS.has(deps.storage, (&contract_addr, &info.sender)) {
+        return Err(ContractError::Unauthorized(
+            "Sender is not a signer for this collection".to_owned(),
+        ));
+    }
+    Ok(contract_addr)
+}
+
+fn assert_registered(deps: Deps, contract: &str) -> Result<Addr, ContractError> {
+    let contract_addr = deps.api.addr_validate(contract)?;
+    if !COLLECTIONS.has(deps.storage, &contract_addr) {
+        return Err(ContractError::CollectionNotRegistered {
+            contract: contract.to_string(),
+        });
+    }
+    Ok(contract_addr)
+}
+
+fn assert_owner(deps: Deps, info: &MessageInfo) -> Result<(), ContractError> {
     let owner = OWNER.load(deps.storage)?;
     if owner != info.sender {
         return Err(ContractError::Unauthorized(
             "Sender is not owner".to_owned(),
         ));
     };
+    Ok(())
+}
 
-    let new_owner = deps.api.addr_validate(&to)?;
-    OWNER.save(deps.storage, &new_owner)?;
+pub fn execute_register_collection(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract: String,
+    signers: Vec<String>,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.as_ref(), &info)?;
+    assert_single_owner(deps.as_ref())?;
+
+    register_collection(deps, contract, signers)
+}
+
+fn register_collection(
+    deps: DepsMut,
+    contract: String,
+    signers: Vec<String>,
+) -> Result<Response, ContractError> {
+    let contract_addr = deps.api.addr_validate(&contract)?;
+    if COLLECTIONS.has(deps.storage, &contract_addr) {
+        return Err(ContractError::CollectionAlreadyRegistered { contract });
+    }
+    // make sure the address is an sg721-imago contract
+    let _: CollectionInfoResponse = deps
+        .querier
+        .query_wasm_smart(&contract_addr, &Imago721QueryMsg::CollectionInfo {})?;
+    COLLECTIONS.save(deps.storage, &contract_addr, &true)?;
+
+    for signer in signers.iter() {
+        let signer_addr = deps.api.addr_validate(signer)?;
+        COLLECTION_SIGNERS.save(deps.storage, (&contract_addr, &signer_addr), &true)?;
+        SIGNER_COLLECTIONS.save(deps.storage, (&signer_addr, &contract_addr), &true)?;
+    }
 
     Ok(Response::default()
-        .add_attribute("action", "finalize_transfer_ownership")
-        .add_attribute("finalize_transfer_ownership", &to)
+        .add_attribute("action", "finalize_register_collection")
+        .add_attribute("finalize_collection", &contract)
+        .add_attribute("finalize_collection_signers", signers.join(","))
     )
 }
 
-pub fn execute_change_signer(
+pub fn execute_deregister_collection(
     deps: DepsMut,
     info: MessageInfo,
-    to: String,
+    contract: String,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.as_ref(), &info)?;
+    assert_single_owner(deps.as_ref())?;
+
+    deregister_collection(deps, contract)
+}
+
+fn deregister_collection(
+    deps: DepsMut,
+    contract: String,
+) -> Result<Response, ContractError> {
+    let contract_addr = assert_registered(deps.as_ref(), &contract)?;
+    COLLECTIONS.remove(deps.storage, &contract_addr);
+
+    let signers = COLLECTION_SIGNERS
+        .prefix(&contract_addr)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<Addr>>>()?;
+    for signer in signers.iter() {
+        COLLECTION_SIGNERS.remove(deps.storage, (&contract_addr, signer));
+        SIGNER_COLLECTIONS.remove(deps.storage, (signer, &contract_addr));
+    }
+    let pubkeys = COLLECTION_SIGNER_PUBKEYS
+        .prefix(&contract_addr)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<Vec<u8>>>>()?;
+    for pubkey in pubkeys.iter() {
+        COLLECTION_SIGNER_PUBKEYS.remove(deps.storage, (&contract_addr, pubkey.as_slice()));
+    }
+
+    Ok(Response::default()
+        .add_attribute("action", "finalize_deregister_collection")
+        .add_attribute("finalize_collection", &contract)
+    )
+}
+
+pub fn execute_change_collection_signer(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract: String,
+    signer: String,
     enabled: bool,
 ) -> Result<Response, ContractError> {
-    let owner = OWNER.load(deps.storage)?;
-    if owner != info.sender {
+    assert_owner(deps.as_ref(), &info)?;
+    assert_single_owner(deps.as_ref())?;
+
+    change_collection_signer(deps, contract, signer, enabled)
+}
+
+fn change_collection_signer(
+    deps: DepsMut,
+    contract: String,
+    signer: String,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    let contract_addr = assert_registered(deps.as_ref(), &contract)?;
+    let signer_addr = deps.api.addr_validate(&signer)?;
+    if enabled {
+        COLLECTION_SIGNERS.save(deps.storage, (&contract_addr, &signer_addr), &true)?;
+        SIGNER_COLLECTIONS.save(deps.storage, (&signer_addr, &contract_addr), &true)?;
+    } else {
+        COLLECTION_SIGNERS.remove(deps.storage, (&contract_addr, &signer_addr));
+        SIGNER_COLLECTIONS.remove(deps.storage, (&signer_addr, &contract_addr));
+    }
+
+    Ok(Response::default()
+        .add_attribute("action", "finalize_change_collection_signer")
+        .add_attribute("finalize_collection", &contract)
+        .add_attribute("finalize_change_signer_to", &signer)
+        .add_attribute("finalize_change_signer_enabled", &enabled.to_string())
+    )
+}
+
+
+// Ownership moves only once the proposed owner accepts, so a typo can't brick the contract
+pub fn execute_propose_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    to: String,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.as_ref(), &info)?;
+    assert_single_owner(deps.as_ref())?;
+
+    propose_ownership(deps, env, to, expires)
+}
+
+fn propose_ownership(
+    deps: DepsMut,
+    env: Env,
+    to: String,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let new_owner = deps.api.addr_validate(&to)?;
+    let expires = expires.unwrap_or_else(|| Expiration::AtTime(env.block.time.plus_seconds(DEFAULT_EXPIRY_SECONDS)));
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::Expired {});
+    }
+    PENDING_OWNER.save(deps.storage, &PendingOwner { owner: new_owner, expires })?;
+
+    Ok(Response::default()
+        .add_attribute("action", "finalize_propose_ownership")
+        .add_attribute("finalize_propose_ownership", &to)
+        .add_attribute("finalize_propose_ownership_expires", expires.to_string())
+    )
+}
+
+pub fn execute_accept_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let pending = PENDING_OWNER
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingOwner {})?;
+    if pending.owner != info.sender {
         return Err(ContractError::Unauthorized(
-            "Sender is not owner".to_owned(),
+            "Sender is not the pending owner".to_owned(),
         ));
+    }
+    if pending.expires.is_expired(&env.block) {
+        return Err(ContractError::Expired {});
+    }
+
+    OWNER.save(deps.storage, &pending.owner)?;
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::default()
+        .add_attribute("action", "finalize_accept_ownership")
+        .add_attribute("finalize_transfer_ownership", pending.owner)
+    )
+}
+
+pub fn execute_cancel_ownership(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.as_ref(), &info)?;
+    assert_single_owner(deps.as_ref())?;
+
+    cancel_ownership(deps)
+}
+
+fn cancel_ownership(deps: DepsMut) -> Result<Response, ContractError> {
+    if PENDING_OWNER.may_load(deps.storage)?.is_none() {
+        return Err(ContractError::NoPendingOwner {});
+    }
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::default()
+        .add_attribute("action", "finalize_cancel_ownership")
+    )
+}
+
+// Passing no owners turns multi-owner mode off. Once on, only a passed proposal can change owners.
+pub fn execute_update_owners(
+    deps: DepsMut,
+    info: MessageInfo,
+    owners: Vec<String>,
+    threshold: u64,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.as_ref(), &info)?;
+    assert_single_owner(deps.as_ref())?;
+
+    update_owners(deps, owners, threshold)
+}
+
+fn update_owners(
+    deps: DepsMut,
+    owners: Vec<String>,
+    threshold: u64,
+) -> Result<Response, ContractError> {
+    if owners.len() > MAX_OWNERS as usize {
+        return Err(ContractError::TooManyOwners { max: MAX_OWNERS });
+    }
+    let owner_addrs = owners
+        .iter()
+        .map(|owner| deps.api.addr_validate(owner))
+        .collect::<StdResult<Vec<Addr>>>()?;
+    let mut unique = owner_addrs.clone();
+    unique.sort();
+    unique.dedup();
+    if !owners.is_empty() && (threshold == 0 || threshold > unique.len() as u64) {
+        return Err(ContractError::InvalidThreshold { threshold, owners: unique.len() as u64 });
+    }
+
+    let turning_on = THRESHOLD.may_load(deps.storage)?.is_none();
+    let current = OWNERS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<Addr>>>()?;
+    for owner in current.iter() {
+        OWNERS.remove(deps.storage, owner);
+    }
+
+    if owners.is_empty() {
+        THRESHOLD.remove(deps.storage);
+    } else {
+        for owner in unique.iter() {
+            OWNERS.save(deps.storage, owner, &true)?;
+        }
+        THRESHOLD.save(deps.storage, &threshold)?;
+        // a transfer started by the single owner needs the owners' approval too
+        if turning_on {
+            PENDING_OWNER.remove(deps.storage);
+        }
+    }
+
+    Ok(Response::default()
+        .add_attribute("action", "finalize_update_owners")
+        .add_attribute("finalize_owners", owners.join(","))
+        .add_attribute("finalize_threshold", threshold.to_string())
+    )
+}
+
+pub fn execute_propose(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    action: ProposalAction,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    assert_multi_owner(deps.as_ref(), &info)?;
+
+    let expires = expires.unwrap_or_else(|| Expiration::AtTime(env.block.time.plus_seconds(DEFAULT_EXPIRY_SECONDS)));
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::Expired {});
+    }
+
+    let id = PROPOSAL_COUNT.may_load(deps.storage)?.unwrap_or(0) + 1;
+    PROPOSAL_COUNT.save(deps.storage, &id)?;
+    PROPOSALS.save(deps.storage, id, &Proposal {
+        proposer: info.sender.clone(),
+        action,
+        expires,
+        executed: false,
+    })?;
+    // the proposer votes yes
+    BALLOTS.save(deps.storage, (id, &info.sender), &true)?;
+
+    Ok(Response::default()
+        .add_attribute("action", "finalize_propose")
+        .add_attribute("finalize_proposal_id", id.to_string())
+        .add_attribute("sender", info.sender)
+    )
+}
+
+pub fn execute_vote(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    assert_multi_owner(deps.as_ref(), &info)?;
+
+    let proposal = load_open_proposal(deps.as_ref(), &env, proposal_id)?;
+    if BALLOTS.has(deps.storage, (proposal_id, &info.sender)) {
+        return Err(ContractError::AlreadyVoted {});
+    }
+    BALLOTS.save(deps.storage, (proposal_id, &info.sender), &true)?;
+
+    Ok(Response::default()
+        .add_attribute("action", "finalize_vote")
+        .add_attribute("finalize_proposal_id", proposal_id.to_string())
+        .add_attribute("finalize_proposer", proposal.proposer)
+        .add_attribute("sender", info.sender)
+    )
+}
+
+pub fn execute_execute_proposal(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    assert_multi_owner(deps.as_ref(), &info)?;
+
+    let mut proposal = load_open_proposal(deps.as_ref(), &env, proposal_id)?;
+    let threshold = THRESHOLD.load(deps.storage)?;
+    let approvals = count_approvals(deps.as_ref(), proposal_id)?;
+    if approvals < threshold {
+        return Err(ContractError::ThresholdNotReached { approvals, threshold });
+    }
+
+    proposal.executed = true;
+    PROPOSALS.save(deps.storage, proposal_id, &proposal)?;
+
+    let res = match proposal.action {
+        ProposalAction::ChangeSigner { to, enabled } => change_signer(deps, to, enabled)?,
+        ProposalAction::RegisterCollection { contract, signers } => register_collection(deps, contract, signers)?,
+        ProposalAction::DeregisterCollection { contract } => deregister_collection(deps, contract)?,
+        ProposalAction::ChangeCollectionSigner { contract, signer, enabled } =>
+            change_collection_signer(deps, contract, signer, enabled)?,
+        ProposalAction::ChangeSignerPubkey { pubkey, enabled } => change_signer_pubkey(deps, pubkey, enabled)?,
+        ProposalAction::ChangeCollectionSignerPubkey { contract, pubkey, enabled } =>
+            change_collection_signer_pubkey(deps, contract, pubkey, enabled)?,
+        ProposalAction::UpdateOwners { owners, threshold } => update_owners(deps, owners, threshold)?,
+        ProposalAction::ProposeOwnership { to, expires } => propose_ownership(deps, env, to, expires)?,
+        ProposalAction::CancelOwnership {} => cancel_ownership(deps)?,
     };
 
+    Ok(res
+        .add_attribute("finalize_proposal_id", proposal_id.to_string())
+        .add_attribute("sender", info.sender)
+    )
+}
+
+fn load_open_proposal(deps: Deps, env: &Env, proposal_id: u64) -> Result<Proposal, ContractError> {
+    let proposal = PROPOSALS
+        .may_load(deps.storage, proposal_id)?
+        .ok_or(ContractError::ProposalNotFound { proposal_id })?;
+    if proposal.executed {
+        return Err(ContractError::ProposalExecuted { proposal_id });
+    }
+    if proposal.expires.is_expired(&env.block) {
+        return Err(ContractError::Expired {});
+    }
+    Ok(proposal)
+}
+
+// Only votes from current owners count, so removed owners can't carry a proposal
+fn count_approvals(deps: Deps, proposal_id: u64) -> StdResult<u64> {
+    let voters = BALLOTS
+        .prefix(proposal_id)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<Addr>>>()?;
+    Ok(voters
+        .iter()
+        .filter(|voter| OWNERS.has(deps.storage, *voter))
+        .count() as u64)
+}
+
+fn assert_single_owner(deps: Deps) -> Result<(), ContractError> {
+    if THRESHOLD.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::ProposalRequired {});
+    }
+    Ok(())
+}
+
+fn assert_multi_owner(deps: Deps, info: &MessageInfo) -> Result<(), ContractError> {
+    if THRESHOLD.may_load(deps.storage)?.is_none() {
+        return Err(ContractError::MultiOwnerDisabled {});
+    }
+    if !OWNERS.has(deps.storage, &info.sender) {
+        return Err(ContractError::Unauthorized(
+            "Sender is not an owner".to_owned(),
+        ));
+    }
+    Ok(())
+}
+
+pub fn execute_change_signer(
+    deps: DepsMut,
+    info: MessageInfo,
+    to: String,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.as_ref(), &info)?;
+    assert_single_owner(deps.as_ref())?;
+
+    change_signer(deps, to, enabled)
+}
+
+fn change_signer(
+    deps: DepsMut,
+    to: String,
+    enabled: bool,
+) -> Result<Response, ContractError> {
     let validated_addr = deps.api.addr_validate(&to)?;
     SIGNERS.save(deps.storage, validated_addr, &enabled)?;
 
@@ -134,29 +746,144 @@ pub fn execute_change_signer(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::Signers { start_after, limit } => to_binary(&query_signers(deps, start_after, limit)?),
+        QueryMsg::Owners {} => to_binary(&query_owners(deps)?),
+        QueryMsg::Proposal { proposal_id } => to_binary(&query_proposal(deps, env, proposal_id)?),
+        QueryMsg::Proposals { start_after, limit } => to_binary(&query_proposals(deps, env, start_after, limit)?),
+        QueryMsg::Nonce { contract, token_id } => to_binary(&query_nonce(deps, contract, token_id)?),
+        QueryMsg::SignersForCollection { contract, start_after, limit } =>
+            to_binary(&query_signers_for_collection(deps, contract, start_after, limit)?),
+        QueryMsg::CollectionsForSigner { signer, start_after, limit } =>
+            to_binary(&query_collections_for_signer(deps, signer, start_after, limit)?),
     }
 }
 
 fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
-    let signers = get_signers(deps);
     let owner = OWNER.load(deps.storage)?;
+    let pending_owner = PENDING_OWNER.may_load(deps.storage)?;
 
     Ok(ConfigResponse {
-        signers,
         owner: owner.to_string(),
+        pending_owner: pending_owner.as_ref().map(|p| p.owner.to_string()),
+        pending_owner_expires: pending_owner.map(|p| p.expires),
     })
 }
 
-fn get_signers(
+fn query_signers(
     deps: Deps,
-) -> Vec<String> {
-    let signers = SIGNERS.range(deps.storage, Option::None, Option::None, Order::Ascending);
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<SignersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_addr = maybe_addr(deps.api, start_after)?;
+    let start = start_addr.map(Bound::exclusive);
 
-    signers
-        .filter(|s| (s.as_ref().unwrap().1))
-        .map(|s| s.unwrap().0.to_string())
-        .collect()
+    let signers = SIGNERS
+        .range(deps.storage, start, None, Order::Ascending)
+        .filter(|item| !matches!(item, Ok((_, false))))
+        .take(limit)
+        .map(|item| item.map(|(signer, _)| signer.to_string()))
+        .collect::<StdResult<_>>()?;
+
+    Ok(SignersResponse { signers })
 }
+
+fn query_owners(deps: Deps) -> StdResult<OwnersResponse> {
+    let owners = OWNERS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .map(|owner| owner.map(|o| o.to_string()))
+        .collect::<StdResult<_>>()?;
+    let threshold = THRESHOLD.may_load(deps.storage)?;
+
+    Ok(OwnersResponse { owners, threshold })
+}
+
+fn query_proposal(deps: Deps, env: Env, proposal_id: u64) -> StdResult<ProposalResponse> {
+    let proposal = PROPOSALS.load(deps.storage, proposal_id)?;
+    to_proposal_response(deps, &env, proposal_id, proposal)
+}
+
+fn query_proposals(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ProposalsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let proposals = PROPOSALS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (id, proposal) = item?;
+            to_proposal_response(deps, &env, id, proposal)
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(ProposalsResponse { proposals })
+}
+
+fn to_proposal_response(deps: Deps, env: &Env, id: u64, proposal: Proposal) -> StdResult<ProposalResponse> {
+    Ok(ProposalResponse {
+        id,
+        proposer: proposal.proposer.to_string(),
+        action: proposal.action,
+        approvals: count_approvals(deps, id)?,
+        expires: proposal.expires,
+        expired: proposal.expires.is_expired(&env.block),
+        executed: proposal.executed,
+    })
+}
+
+fn query_nonce(deps: Deps, contract: String, token_id: String) -> StdResult<NonceResponse> {
+    let contract = deps.api.addr_validate(&contract)?;
+    let nonce = NONCES.may_load(deps.storage, (&contract, &token_id))?.unwrap_or(0);
+    Ok(NonceResponse { nonce })
+}
+
+fn query_signers_for_collection(
+    deps: Deps,
+    contract: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<SignersResponse> {
+    let contract = deps.api.addr_validate(&contract)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_addr = maybe_addr(deps.api, start_after)?;
+    let start = start_addr.as_ref().map(Bound::exclusive);
+
+    let signers = COLLECTION_SIGNERS
+        .prefix(&contract)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|signer| signer.map(|s| s.to_string()))
+        .collect::<StdResult<_>>()?;
+
+    Ok(SignersResponse { signers })
+}
+
+fn query_collections_for_signer(
+    deps: Deps,
+    signer: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<CollectionsResponse> {
+    let signer = deps.api.addr_validate(&signer)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_addr = maybe_addr(deps.api, start_after)?;
+    let start = start_addr.as_ref().map(Bound::exclusive);
+
+    let collections = SIGNER_COLLECTIONS
+        .prefix(&signer)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|collection| collection.map(|c| c.to_string()))
+        .collect::<StdResult<_>>()?;
+
+    Ok(CollectionsResponse { collections })
+}
+
@@ -77,6 +77,54 @@ pub enum ContractError {
     #[error("ZeroBalance")]
     ZeroBalance {},
 
+    #[error("No tokens to finalize")]
+    EmptyBatch {},
+
+    #[error("Too many tokens to finalize, max: {max}")]
+    BatchTooLarge { max: u32 },
+
+    #[error("Invalid public key")]
+    InvalidPubkey {},
+
+    #[error("Invalid signature")]
+    InvalidSignature {},
+
+    #[error("Collection not registered: {contract}")]
+    CollectionNotRegistered { contract: String },
+
+    #[error("Collection already registered: {contract}")]
+    CollectionAlreadyRegistered { contract: String },
+
+    #[error("Expired")]
+    Expired {},
+
+    #[error("No pending owner")]
+    NoPendingOwner {},
+
+    #[error("Too many owners, max: {max}")]
+    TooManyOwners { max: u32 },
+
+    #[error("Invalid threshold {threshold} for {owners} owners")]
+    InvalidThreshold { threshold: u64, owners: u64 },
+
+    #[error("Multi-owner mode is on, submit a proposal")]
+    ProposalRequired {},
+
+    #[error("Multi-owner mode is off")]
+    MultiOwnerDisabled {},
+
+    #[error("Proposal {proposal_id} not found")]
+    ProposalNotFound { proposal_id: u64 },
+
+    #[error("Proposal {proposal_id} already executed")]
+    ProposalExecuted { proposal_id: u64 },
+
+    #[error("Already voted")]
+    AlreadyVoted {},
+
+    #[error("Threshold not reached, {approvals} of {threshold} approvals")]
+    ThresholdNotReached { approvals: u64, threshold: u64 },
+
     #[error("{0}")]
     Payment(#[from] PaymentError),
 }
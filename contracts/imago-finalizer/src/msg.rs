@@ -1,3 +1,5 @@
+use cosmwasm_std::Binary;
+use cw_utils::Expiration;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -10,19 +12,106 @@ pub struct InstantiateMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
-    TransferOwnership { to: String },
+    /// Propose a new owner, who has to accept before it expires (default one week).
+    /// In multi-owner mode this and `CancelOwnership` go through `Propose`/`Vote`/`Execute`.
+    ProposeOwnership { to: String, expires: Option<Expiration> },
+    AcceptOwnership {},
+    CancelOwnership {},
+    /// Turn on M-of-N owners for signer changes, an empty list turns it off.
+    /// Once on, collection, signer, owner and ownership changes go through `Propose`/`Vote`/`Execute`.
+    UpdateOwners { owners: Vec<String>, threshold: u64 },
+    Propose { action: ProposalAction, expires: Option<Expiration> },
+    Vote { proposal_id: u64 },
+    Execute { proposal_id: u64 },
     ChangeSigner { to: String, enabled: bool },
+    /// Allow finalizing an sg721-imago contract, scoped to the given signers
+    RegisterCollection { contract: String, signers: Vec<String> },
+    /// Remove a collection and every signer scoped to it
+    DeregisterCollection { contract: String },
+    /// Add or remove a signer from a registered collection
+    ChangeCollectionSigner { contract: String, signer: String, enabled: bool },
     Finalize { contract:String, token_id: String, token_uri: String },
+    /// Finalize many (token_id, token_uri) pairs on one contract in a single tx, at most 50
+    FinalizeBatch { contract: String, items: Vec<(String, String)> },
+    /// Enable or disable a secp256k1 public key (33 byte compressed or 65 byte uncompressed)
+    ChangeSignerPubkey { pubkey: Binary, enabled: bool },
+    /// Add or remove a public key from a registered collection
+    ChangeCollectionSignerPubkey { contract: String, pubkey: Binary, enabled: bool },
+    /// Relay a finalization signed off-chain by an enabled signer public key scoped to the collection.
+    /// The signature is over sha256 of the payload built by `finalize_payload`.
+    FinalizeWithSignature { contract: String, token_id: String, token_uri: String, pubkey: Binary, signature: Binary },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    /// Enabled signers
+    Signers { start_after: Option<String>, limit: Option<u32> },
+    Owners {},
+    Proposal { proposal_id: u64 },
+    Proposals { start_after: Option<u64>, limit: Option<u32> },
+    /// Nonce expected in the next signed finalization of a token
+    Nonce { contract: String, token_id: String },
+    SignersForCollection { contract: String, start_after: Option<String>, limit: Option<u32> },
+    CollectionsForSigner { signer: String, start_after: Option<String>, limit: Option<u32> },
+}
+
+/// Changes that need M-of-N owner approval in multi-owner mode
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalAction {
+    ChangeSigner { to: String, enabled: bool },
+    RegisterCollection { contract: String, signers: Vec<String> },
+    DeregisterCollection { contract: String },
+    ChangeCollectionSigner { contract: String, signer: String, enabled: bool },
+    ChangeSignerPubkey { pubkey: Binary, enabled: bool },
+    ChangeCollectionSignerPubkey { contract: String, pubkey: Binary, enabled: bool },
+    UpdateOwners { owners: Vec<String>, threshold: u64 },
+    ProposeOwnership { to: String, expires: Option<Expiration> },
+    CancelOwnership {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
     pub owner: String,
+    pub pending_owner: Option<String>,
+    pub pending_owner_expires: Option<Expiration>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OwnersResponse {
+    pub owners: Vec<String>,
+    pub threshold: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProposalResponse {
+    pub id: u64,
+    pub proposer: String,
+    pub action: ProposalAction,
+    pub approvals: u64,
+    pub expires: Expiration,
+    pub expired: bool,
+    pub executed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProposalsResponse {
+    pub proposals: Vec<ProposalResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NonceResponse {
+    pub nonce: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SignersResponse {
     pub signers: Vec<String>,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollectionsResponse {
+    pub collections: Vec<String>,
+}
@@ -1,5 +1,44 @@
 use cosmwasm_std::{Addr};
 use cw_storage_plus::{Item, Map};
+use cw_utils::Expiration;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::msg::ProposalAction;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingOwner {
+    pub owner: Addr,
+    pub expires: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Proposal {
+    pub proposer: Addr,
+    pub action: ProposalAction,
+    pub expires: Expiration,
+    pub executed: bool,
+}
 
 pub const OWNER: Item<Addr> = Item::new("owner");
+pub const PENDING_OWNER: Item<PendingOwner> = Item::new("pending_owner");
+// multi-owner mode is on while THRESHOLD is set
+pub const OWNERS: Map<&Addr, bool> = Map::new("owners");
+pub const THRESHOLD: Item<u64> = Item::new("threshold");
+pub const PROPOSAL_COUNT: Item<u64> = Item::new("proposal_count");
+pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
+// (proposal_id, owner) yes votes
+pub const BALLOTS: Map<(u64, &Addr), bool> = Map::new("ballots");
 pub const SIGNERS: Map<Addr, bool> = Map::new("signers");
+// sg721-imago contracts this finalizer may finalize
+pub const COLLECTIONS: Map<&Addr, bool> = Map::new("collections");
+// (collection, signer) pairs, a signer can only finalize the collections it is scoped to
+pub const COLLECTION_SIGNERS: Map<(&Addr, &Addr), bool> = Map::new("collection_signers");
+// reverse index of COLLECTION_SIGNERS, (signer, collection)
+pub const SIGNER_COLLECTIONS: Map<(&Addr, &Addr), bool> = Map::new("signer_collections");
+// secp256k1 public keys allowed to sign relayed finalizations
+pub const SIGNER_PUBKEYS: Map<&[u8], bool> = Map::new("signer_pubkeys");
+// (collection, pubkey) pairs, a public key can only sign for the collections it is scoped to
+pub const COLLECTION_SIGNER_PUBKEYS: Map<(&Addr, &[u8]), bool> = Map::new("collection_signer_pubkeys");
+// next expected nonce per (contract, token_id) for relayed finalizations
+pub const NONCES: Map<(&Addr, &str), u64> = Map::new("nonces");
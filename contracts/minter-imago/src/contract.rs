@@ -2,14 +2,14 @@ use cosmwasm_std::{Addr, BankMsg, Binary, coin, Coin, coins, CosmosMsg, Decimal,
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cw2::set_contract_version;
-use cw721_base::{MintMsg, msg::ExecuteMsg as Cw721ExecuteMsg};
+use cw721_base::MintMsg;
 use cw_utils::{may_pay, maybe_addr, must_pay, nonpayable, parse_reply_instantiate_data};
 use semver::Version;
 use sg1::{FeeError};
 use sg_std::{GENESIS_MINT_START_TIME, NATIVE_DENOM, StargazeMsgWrapper};
 use url::Url;
 
-use sg721_imago::msg::{CollectionInfoResponse, InstantiateMsg as Sg721InstantiateMsg};
+use sg721_imago::msg::{CollectionInfoResponse, ExecuteMsg as Sg721ExecuteMsg, InstantiateMsg as Sg721InstantiateMsg};
 use sg721_imago::msg::QueryMsg::CollectionInfo;
 use whitelist::msg::{
     ConfigResponse as WhitelistConfigResponse, HasMemberResponse, QueryMsg as WhitelistQueryMsg,
@@ -382,7 +382,7 @@ fn _execute_mint(
         None => info.sender.clone(),
     };
 
-    let mint_price: Coin = mint_price(deps.as_ref(), env.clone(), is_admin)?;
+    let mint_price: Coin = mint_price(deps.as_ref(), env, is_admin)?;
     // Exact payment only accepted
     let payment = may_pay(&info, &config.unit_price.denom)?;
 
@@ -434,8 +434,8 @@ fn _execute_mint(
     }
     let mintable_token_id = mintable_tokens[0];
 
-    // Create mint msgs
-    let mint_msg = Cw721ExecuteMsg::Mint(MintMsg::<Empty> {
+    // Create mint msgs, the collection derives the token's seed
+    let mint_msg = Sg721ExecuteMsg::Mint(MintMsg::<Empty> {
         token_id: mintable_token_id.to_string(),
         owner: recipient_addr.to_string(),
         token_uri: Some(format!("{}/{}", config.base_token_uri, mintable_token_id)),
@@ -554,9 +554,21 @@ pub fn execute_burn_remaining(
     config.num_tokens = num_tokens;
     CONFIG.save(deps.storage, &config)?;
 
+    // No more tokens can be minted, freeze the generative code
+    let sg721_address = SG721_ADDRESS.load(deps.storage)?;
+
     Ok(Response::default()
         .add_attribute("action", "burn_remaining")
-        .add_attribute("sender", info.sender))
+        .add_attribute("sender", info.sender)
+        .add_message(lock_code_uri_msg(&sg721_address)?))
+}
+
+fn lock_code_uri_msg(sg721_address: &Addr) -> StdResult<CosmosMsg<StargazeMsgWrapper>> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: sg721_address.to_string(),
+        msg: to_binary(&Sg721ExecuteMsg::LockCodeUri {})?,
+        funds: vec![],
+    }))
 }
 
 pub fn execute_update_start_time(
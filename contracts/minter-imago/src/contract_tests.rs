@@ -1,4 +1,4 @@
-use cosmwasm_std::{Addr, coin, coins, Decimal, Timestamp, Uint128};
+use cosmwasm_std::{Addr, coin, coins, Decimal, Timestamp, TransactionInfo, Uint128};
 use cosmwasm_std::{Api, Coin};
 use cosmwasm_std::testing::{mock_dependencies_with_balance, mock_env, mock_info};
 use cw721::{Cw721QueryMsg, OwnerOfResponse};
@@ -6,7 +6,8 @@ use cw_multi_test::{BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
 use sg_multi_test::StargazeApp;
 use sg_std::{GENESIS_MINT_START_TIME, NATIVE_DENOM, StargazeMsgWrapper};
 
-use sg721_imago::msg::{CodeUriResponse, InstantiateMsg as Sg721InstantiateMsg, QueryMsg as Sg721ImagoQueryMsg, RoyaltyInfoResponse};
+use sg721_imago::contract::token_seed;
+use sg721_imago::msg::{CodeUriResponse, InstantiateMsg as Sg721InstantiateMsg, QueryMsg as Sg721ImagoQueryMsg, RoyaltyInfoResponse, TokenSeedResponse};
 use sg721_imago::state::CollectionInfo;
 
 use crate::contract::{dutch_auction_price_at_time, instantiate};
@@ -699,6 +700,21 @@ fn happy_path() {
         .unwrap();
     assert_eq!(res.owner, buyer.to_string());
 
+    // Seed is stored on the token at mint
+    let res: TokenSeedResponse = router
+        .wrap()
+        .query_wasm_smart(
+            config.sg721_address.clone(),
+            &Sg721ImagoQueryMsg::TokenSeed {
+                token_id: String::from("1"),
+            },
+        )
+        .unwrap();
+    let mut env = mock_env();
+    env.block = router.block_info();
+    env.transaction = Some(TransactionInfo { index: 0 });
+    assert_eq!(res.seed, token_seed(&env, "1", &buyer));
+
     // Buyer can't call MintTo
     let mint_to_msg = ExecuteMsg::MintTo {
         recipient: buyer.to_string(),
@@ -811,6 +827,8 @@ fn happy_path() {
         )
         .unwrap();
     assert_eq!(res.code_uri, "ipfs://test_code_url");
+    // Code URI is locked by the first mint
+    assert!(res.locked);
 }
 
 
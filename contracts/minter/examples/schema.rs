@@ -3,7 +3,7 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
-use minter::msg::{ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
+use minter::msg::{ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, StartingIndexResponse};
 use minter::state::Config;
 
 fn main() {
@@ -17,4 +17,5 @@ fn main() {
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(Config), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(StartingIndexResponse), &out_dir);
 }
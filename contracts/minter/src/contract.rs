@@ -8,16 +8,18 @@ use cw2::set_contract_version;
 use cw721_base::{msg::ExecuteMsg as Cw721ExecuteMsg, MintMsg};
 use cw_utils::{may_pay, parse_reply_instantiate_data};
 use sg1::checked_fair_burn;
-use sg721::msg::InstantiateMsg as Sg721InstantiateMsg;
+use sg721::msg::{ExecuteMsg as Sg721ExecuteMsg, InstantiateMsg as Sg721InstantiateMsg};
+use sha2::{Digest, Sha256};
 use url::Url;
 
 use crate::error::ContractError;
 use crate::msg::{
     ConfigResponse, ExecuteMsg, InstantiateMsg, MintCountResponse, MintPriceResponse,
-    MintableNumTokensResponse, QueryMsg, StartTimeResponse,
+    MintableNumTokensResponse, QueryMsg, StartTimeResponse, StartingIndexResponse,
 };
 use crate::state::{
     Config, CONFIG, MINTABLE_NUM_TOKENS, MINTABLE_TOKEN_IDS, MINTER_ADDRS, SG721_ADDRESS,
+    STARTING_INDEX, STARTING_INDEX_BLOCK, STARTING_INDEX_SEED,
 };
 use sg_std::{StargazeMsgWrapper, GENESIS_MINT_START_TIME, NATIVE_DENOM};
 use whitelist::msg::{
@@ -134,6 +136,7 @@ pub fn instantiate(
                 name: msg.sg721_instantiate_msg.name,
                 symbol: msg.sg721_instantiate_msg.symbol,
                 minter: env.contract.address.to_string(),
+                provenance_hash: msg.sg721_instantiate_msg.provenance_hash,
                 collection_info: msg.sg721_instantiate_msg.collection_info,
             })?,
             funds: info.funds,
@@ -167,15 +170,16 @@ pub fn execute(
         ExecuteMsg::UpdatePerAddressLimit { per_address_limit } => {
             execute_update_per_address_limit(deps, env, info, per_address_limit)
         }
-        ExecuteMsg::MintTo { recipient } => execute_mint_to(deps, info, recipient),
+        ExecuteMsg::MintTo { recipient } => execute_mint_to(deps, env, info, recipient),
         ExecuteMsg::MintFor {
             token_id,
             recipient,
-        } => execute_mint_for(deps, info, token_id, recipient),
+        } => execute_mint_for(deps, env, info, token_id, recipient),
         ExecuteMsg::SetWhitelist { whitelist } => {
             execute_set_whitelist(deps, env, info, &whitelist)
         }
         ExecuteMsg::Withdraw {} => execute_withdraw(deps, env, info),
+        ExecuteMsg::SetStartingIndex {} => execute_set_starting_index(deps, env),
     }
 }
 
@@ -265,7 +269,7 @@ pub fn execute_mint_sender(
         return Err(ContractError::MaxPerAddressLimitExceeded {});
     }
 
-    _execute_mint(deps, info, action, false, None, None)
+    _execute_mint(deps, env, info, action, false, None, None)
 }
 
 // Check if a whitelist exists and not ended
@@ -311,6 +315,7 @@ fn is_public_mint(deps: Deps, info: &MessageInfo) -> Result<bool, ContractError>
 
 pub fn execute_mint_to(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     recipient: String,
 ) -> Result<Response, ContractError> {
@@ -325,11 +330,12 @@ pub fn execute_mint_to(
         ));
     }
 
-    _execute_mint(deps, info, action, true, Some(recipient), None)
+    _execute_mint(deps, env, info, action, true, Some(recipient), None)
 }
 
 pub fn execute_mint_for(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     token_id: u32,
     recipient: String,
@@ -345,7 +351,15 @@ pub fn execute_mint_for(
         ));
     }
 
-    _execute_mint(deps, info, action, true, Some(recipient), Some(token_id))
+    _execute_mint(
+        deps,
+        env,
+        info,
+        action,
+        true,
+        Some(recipient),
+        Some(token_id),
+    )
 }
 
 // Generalize checks and mint message creation
@@ -354,6 +368,7 @@ pub fn execute_mint_for(
 // mint_for(recipient: "friend2", token_id: 420) -> _execute_mint(recipient, token_id)
 fn _execute_mint(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     action: &str,
     is_admin: bool,
@@ -432,6 +447,12 @@ fn _execute_mint(
     let mintable_num_tokens = MINTABLE_NUM_TOKENS.load(deps.storage)?;
     // Decrement mintable num tokens
     MINTABLE_NUM_TOKENS.save(deps.storage, &(mintable_num_tokens - 1))?;
+    // Record the sellout block and fix the seed of the starting index with it
+    if mintable_num_tokens == 1 {
+        STARTING_INDEX_BLOCK.save(deps.storage, &env.block.height)?;
+        let seed = starting_index_seed(&env, &info.sender, mintable_token_id);
+        STARTING_INDEX_SEED.save(deps.storage, &seed)?;
+    }
     // Save the new mint count for the sender's address
     let new_mint_count = mint_count(deps.as_ref(), &info)? + 1;
     MINTER_ADDRS.save(deps.storage, info.clone().sender, &new_mint_count)?;
@@ -446,6 +467,58 @@ fn _execute_mint(
         .add_messages(msgs))
 }
 
+/// Hash of the block and the mint that sold out the collection. None of it is known when the
+/// provenance hash is committed.
+pub fn starting_index_seed(env: &Env, minter: &Addr, token_id: u32) -> u64 {
+    let mut hasher = Sha256::new();
+    hasher.update(env.block.height.to_be_bytes());
+    hasher.update(env.block.time.nanos().to_be_bytes());
+    hasher.update(env.contract.address.as_bytes());
+    hasher.update(minter.as_bytes());
+    hasher.update(token_id.to_be_bytes());
+    let hash = hasher.finalize();
+    let mut seed = [0u8; 8];
+    seed.copy_from_slice(&hash[0..8]);
+    u64::from_be_bytes(seed)
+}
+
+// Anyone can trigger this once the collection has sold out and the sellout block has passed.
+// The offset only depends on the seed fixed at sellout, not on the block the call lands in.
+pub fn execute_set_starting_index(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if STARTING_INDEX.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::StartingIndexAlreadySet {});
+    }
+    let starting_index_block = STARTING_INDEX_BLOCK
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NotSoldOut {})?;
+    if env.block.height <= starting_index_block {
+        return Err(ContractError::StartingIndexBlockNotPassed {
+            starting_index_block,
+        });
+    }
+
+    let sg721_address = SG721_ADDRESS.load(deps.storage)?;
+
+    let seed = STARTING_INDEX_SEED.load(deps.storage)?;
+    let starting_index = (seed % config.num_tokens as u64) as u32;
+    STARTING_INDEX.save(deps.storage, &starting_index)?;
+
+    let msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: sg721_address.to_string(),
+        msg: to_binary(&Sg721ExecuteMsg::SetStartingIndex {
+            starting_index,
+            num_tokens: config.num_tokens,
+        })?,
+        funds: vec![],
+    });
+
+    Ok(Response::new()
+        .add_attribute("action", "set_starting_index")
+        .add_attribute("starting_index", starting_index.to_string())
+        .add_message(msg))
+}
+
 pub fn execute_update_start_time(
     deps: DepsMut,
     env: Env,
@@ -552,6 +625,7 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::MintableNumTokens {} => to_binary(&query_mintable_num_tokens(deps)?),
         QueryMsg::MintPrice {} => to_binary(&query_mint_price(deps)?),
         QueryMsg::MintCount { address } => to_binary(&query_mint_count(deps, address)?),
+        QueryMsg::StartingIndex {} => to_binary(&query_starting_index(deps)?),
     }
 }
 
@@ -612,6 +686,13 @@ fn query_mint_price(deps: Deps) -> StdResult<MintPriceResponse> {
     })
 }
 
+fn query_starting_index(deps: Deps) -> StdResult<StartingIndexResponse> {
+    Ok(StartingIndexResponse {
+        starting_index: STARTING_INDEX.may_load(deps.storage)?,
+        starting_index_block: STARTING_INDEX_BLOCK.may_load(deps.storage)?,
+    })
+}
+
 // Reply callback triggered from cw721 contract instantiation
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
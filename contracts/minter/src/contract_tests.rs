@@ -4,17 +4,20 @@ use cosmwasm_std::{Api, Coin};
 use cw721::{Cw721QueryMsg, OwnerOfResponse};
 use cw721_base::ExecuteMsg as Cw721ExecuteMsg;
 use cw_multi_test::{BankSudo, Contract, ContractWrapper, Executor, SudoMsg};
-use sg721::msg::{InstantiateMsg as Sg721InstantiateMsg, RoyaltyInfoResponse};
+use sg721::msg::{
+    InstantiateMsg as Sg721InstantiateMsg, ProvenanceResponse, QueryMsg as Sg721QueryMsg,
+    RoyaltyInfoResponse,
+};
 use sg721::state::CollectionInfo;
 use sg_multi_test::StargazeApp;
 use sg_std::{StargazeMsgWrapper, GENESIS_MINT_START_TIME, NATIVE_DENOM};
 use whitelist::msg::InstantiateMsg as WhitelistInstantiateMsg;
 use whitelist::msg::{AddMembersMsg, ExecuteMsg as WhitelistExecuteMsg};
 
-use crate::contract::instantiate;
+use crate::contract::{instantiate, starting_index_seed};
 use crate::msg::{
     ConfigResponse, ExecuteMsg, InstantiateMsg, MintCountResponse, MintPriceResponse,
-    MintableNumTokensResponse, QueryMsg, StartTimeResponse,
+    MintableNumTokensResponse, QueryMsg, StartTimeResponse, StartingIndexResponse,
 };
 use crate::ContractError;
 
@@ -106,6 +109,7 @@ fn setup_minter_contract(
             name: String::from("TEST"),
             symbol: String::from("TEST"),
             minter: creator.to_string(),
+            provenance_hash: None,
             collection_info: CollectionInfo {
                 creator: creator.to_string(),
                 description: String::from("Stargaze Monkeys"),
@@ -215,6 +219,7 @@ fn initialization() {
             name: String::from("TEST"),
             symbol: String::from("TEST"),
             minter: info.sender.to_string(),
+            provenance_hash: None,
             collection_info: CollectionInfo {
                 creator: info.sender.to_string(),
                 description: String::from("Stargaze Monkeys"),
@@ -243,6 +248,7 @@ fn initialization() {
             name: String::from("TEST"),
             symbol: String::from("TEST"),
             minter: info.sender.to_string(),
+            provenance_hash: None,
             collection_info: CollectionInfo {
                 creator: info.sender.to_string(),
                 description: String::from("Stargaze Monkeys"),
@@ -272,6 +278,7 @@ fn initialization() {
             name: String::from("TEST"),
             symbol: String::from("TEST"),
             minter: info.sender.to_string(),
+            provenance_hash: None,
             collection_info: CollectionInfo {
                 creator: info.sender.to_string(),
                 description: String::from("Stargaze Monkeys"),
@@ -300,6 +307,7 @@ fn initialization() {
             name: String::from("TEST"),
             symbol: String::from("TEST"),
             minter: info.sender.to_string(),
+            provenance_hash: None,
             collection_info: CollectionInfo {
                 creator: info.sender.to_string(),
                 description: String::from("Stargaze Monkeys"),
@@ -328,6 +336,7 @@ fn initialization() {
             name: String::from("TEST"),
             symbol: String::from("TEST"),
             minter: info.sender.to_string(),
+            provenance_hash: None,
             collection_info: CollectionInfo {
                 creator: info.sender.to_string(),
                 description: String::from("Stargaze Monkeys"),
@@ -356,6 +365,7 @@ fn initialization() {
             name: String::from("TEST"),
             symbol: String::from("TEST"),
             minter: info.sender.to_string(),
+            provenance_hash: None,
             collection_info: CollectionInfo {
                 creator: info.sender.to_string(),
                 description: String::from("Stargaze Monkeys"),
@@ -1234,6 +1244,7 @@ fn test_start_time_before_genesis() {
             name: String::from("TEST"),
             symbol: String::from("TEST"),
             minter: creator.to_string(),
+            provenance_hash: None,
             collection_info: CollectionInfo {
                 creator: creator.to_string(),
                 description: String::from("Stargaze Monkeys"),
@@ -1284,6 +1295,7 @@ fn test_update_start_time() {
             name: String::from("TEST"),
             symbol: String::from("TEST"),
             minter: creator.to_string(),
+            provenance_hash: None,
             collection_info: CollectionInfo {
                 creator: creator.to_string(),
                 description: String::from("Stargaze Monkeys"),
@@ -1342,6 +1354,7 @@ fn test_invalid_start_time() {
             name: String::from("TEST"),
             symbol: String::from("TEST"),
             minter: creator.to_string(),
+            provenance_hash: None,
             collection_info: CollectionInfo {
                 creator: creator.to_string(),
                 description: String::from("Stargaze Monkeys"),
@@ -1509,3 +1522,105 @@ fn can_withdraw() {
         coins(INITIAL_BALANCE + UNIT_PRICE - MINT_FEE, NATIVE_DENOM)
     );
 }
+
+#[test]
+fn starting_index_after_sellout() {
+    let mut router = custom_mock_app();
+    setup_block_time(&mut router, GENESIS_MINT_START_TIME + 1);
+    let (creator, buyer) = setup_accounts(&mut router);
+    let num_tokens = 2;
+    let (minter_addr, config) = setup_minter_contract(&mut router, &creator, num_tokens);
+
+    // Can't set starting index before sellout
+    let set_starting_index_msg = ExecuteMsg::SetStartingIndex {};
+    let err = router
+        .execute_contract(
+            creator.clone(),
+            minter_addr.clone(),
+            &set_starting_index_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NotSoldOut {}
+    );
+
+    let mint_msg = ExecuteMsg::Mint {};
+    for _ in 0..num_tokens {
+        let res = router.execute_contract(
+            buyer.clone(),
+            minter_addr.clone(),
+            &mint_msg,
+            &coins(UNIT_PRICE, NATIVE_DENOM),
+        );
+        assert!(res.is_ok());
+    }
+
+    let sellout_block = router.block_info().height;
+    let mut sellout_env = mock_env();
+    sellout_env.block = router.block_info();
+    sellout_env.contract.address = minter_addr.clone();
+    let res: StartingIndexResponse = router
+        .wrap()
+        .query_wasm_smart(minter_addr.clone(), &QueryMsg::StartingIndex {})
+        .unwrap();
+    assert_eq!(res.starting_index, None);
+    assert_eq!(res.starting_index_block, Some(sellout_block));
+
+    // Can't set starting index in the sellout block
+    let err = router
+        .execute_contract(
+            creator.clone(),
+            minter_addr.clone(),
+            &set_starting_index_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::StartingIndexBlockNotPassed {
+            starting_index_block: sellout_block
+        }
+    );
+
+    let mut block = router.block_info();
+    block.height += 10;
+    router.set_block(block);
+
+    // Anyone can set starting index once the sellout block has passed
+    let res = router.execute_contract(
+        buyer.clone(),
+        minter_addr.clone(),
+        &set_starting_index_msg,
+        &[],
+    );
+    assert!(res.is_ok());
+
+    let res: StartingIndexResponse = router
+        .wrap()
+        .query_wasm_smart(minter_addr.clone(), &QueryMsg::StartingIndex {})
+        .unwrap();
+    let starting_index = res.starting_index.unwrap();
+    assert!(starting_index < num_tokens);
+    // It only depends on the sellout mint, not on the block it was set in
+    let seed = starting_index_seed(&sellout_env, &buyer, num_tokens);
+    assert_eq!(starting_index as u64, seed % num_tokens as u64);
+
+    // Starting index is forwarded to the collection
+    let res: ProvenanceResponse = router
+        .wrap()
+        .query_wasm_smart(config.sg721_address, &Sg721QueryMsg::Provenance {})
+        .unwrap();
+    assert_eq!(res.provenance_hash, None);
+    assert_eq!(res.starting_index, Some(starting_index));
+
+    // Can only be set once
+    let err = router
+        .execute_contract(creator, minter_addr, &set_starting_index_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::StartingIndexAlreadySet {}
+    );
+}
@@ -75,6 +75,15 @@ pub enum ContractError {
     #[error("Token id: {token_id} already sold")]
     TokenIdAlreadySold { token_id: u32 },
 
+    #[error("Collection has not sold out yet")]
+    NotSoldOut {},
+
+    #[error("Starting index already set")]
+    StartingIndexAlreadySet {},
+
+    #[error("Starting index block {starting_index_block} has not passed")]
+    StartingIndexBlockNotPassed { starting_index_block: u64 },
+
     #[error("ZeroBalance")]
     ZeroBalance {},
 
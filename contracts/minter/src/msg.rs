@@ -26,6 +26,7 @@ pub enum ExecuteMsg {
     MintTo { recipient: String },
     MintFor { token_id: u32, recipient: String },
     Withdraw {},
+    SetStartingIndex {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -36,6 +37,7 @@ pub enum QueryMsg {
     StartTime {},
     MintPrice {},
     MintCount { address: String },
+    StartingIndex {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -73,3 +75,9 @@ pub struct MintCountResponse {
     pub address: String,
     pub count: u32,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StartingIndexResponse {
+    pub starting_index: Option<u32>,
+    pub starting_index_block: Option<u64>,
+}
@@ -21,3 +21,7 @@ pub const SG721_ADDRESS: Item<Addr> = Item::new("sg721_address");
 pub const MINTABLE_TOKEN_IDS: Map<u32, bool> = Map::new("mt");
 pub const MINTABLE_NUM_TOKENS: Item<u32> = Item::new("mintable_num_tokens");
 pub const MINTER_ADDRS: Map<Addr, u32> = Map::new("ma");
+pub const STARTING_INDEX_BLOCK: Item<u64> = Item::new("starting_index_block");
+/// Hash of the sellout block and mint, the starting index is derived from it
+pub const STARTING_INDEX_SEED: Item<u64> = Item::new("starting_index_seed");
+pub const STARTING_INDEX: Item<u32> = Item::new("starting_index");
@@ -4,7 +4,11 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, export_schema_with_title, remove_schemas, schema_for};
 
 pub use cw4::{AdminResponse, MemberListResponse, MemberResponse, TotalWeightResponse};
-pub use royalty_group::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+pub use royalty_group::hook::DistributionExecuteMsg;
+pub use royalty_group::msg::{
+    ClaimableResponse, ConfigResponse, DenomsResponse, ExecuteMsg, HistoryResponse, InstantiateMsg,
+    QueryMsg, WeightReductionResponse,
+};
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -19,4 +23,10 @@ fn main() {
     export_schema(&schema_for!(MemberListResponse), &out_dir);
     export_schema(&schema_for!(MemberResponse), &out_dir);
     export_schema(&schema_for!(TotalWeightResponse), &out_dir);
+    export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(ClaimableResponse), &out_dir);
+    export_schema(&schema_for!(DenomsResponse), &out_dir);
+    export_schema(&schema_for!(WeightReductionResponse), &out_dir);
+    export_schema(&schema_for!(HistoryResponse), &out_dir);
+    export_schema(&schema_for!(DistributionExecuteMsg), &out_dir);
 }
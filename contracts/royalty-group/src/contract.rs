@@ -1,25 +1,43 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, coin, to_binary, Addr, BankMsg, Binary, Coin, Deps, DepsMut, Env, MessageInfo, Order,
-    Response, StdResult, SubMsg, Uint128,
+    attr, to_binary, Addr, Api, BankMsg, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env,
+    MessageInfo, Order, Reply, Response, StdResult, Storage, SubMsgResult, Uint128, WasmMsg,
 };
 use cw2::set_contract_version;
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
 use cw4::{
     Member, MemberChangedHookMsg, MemberDiff, MemberListResponse, MemberResponse,
     TotalWeightResponse,
 };
 use cw_storage_plus::Bound;
-use cw_utils::maybe_addr;
+use cw_utils::{maybe_addr, Expiration};
+use sg_controllers::Hooks;
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{ADMIN, HOOKS, MEMBERS, TOTAL};
+use crate::hook::{DistributionHookMsg, MemberShare};
+use crate::msg::{
+    ClaimableResponse, ConfigResponse, DenomsResponse, DistributionMode, ExecuteMsg,
+    HistoryResponse, InstantiateMsg, MigrateMsg, QueryMsg, WeightChange, WeightReductionResponse,
+};
+use crate::state::{
+    Config, ADMIN, CLAIMABLE, CONFIG, DENOMS, DISTRIBUTION_HOOKS, HOOKS, MEMBERS, MEMBER_INDEX,
+    OUTSTANDING, REDUCTION_APPROVALS, REWARD_INDEX, ROUNDING_DUST, TOTAL,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:royalty-group";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// CW20 tokens are tracked under the denom `cw20:<contract>`
+pub const CW20_PREFIX: &str = "cw20:";
+
+// every denom with a reward index is settled when a weight changes, so their number is capped
+const MAX_DENOMS: u32 = 20;
+
+const DISTRIBUTION_HOOK_REPLY_ID: u64 = 1;
+const MEMBER_HOOK_REPLY_ID: u64 = 2;
+
 // Note, you can use StdResult in some functions where you do not
 // make use of the custom errors
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -30,7 +48,15 @@ pub fn instantiate(
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-    create(deps, msg.admin, msg.members, env.block.height)?;
+    create(
+        deps,
+        msg.admin,
+        msg.members,
+        msg.mode.unwrap_or(DistributionMode::Push),
+        msg.lock,
+        msg.denoms,
+        env.block.height,
+    )?;
     Ok(Response::default()
         .add_attribute("action", "instantiate")
         .add_attribute("contract_name", CONTRACT_NAME)
@@ -43,12 +69,17 @@ pub fn create(
     mut deps: DepsMut,
     admin: Option<String>,
     members: Vec<Member>,
+    mode: DistributionMode,
+    lock: Option<Expiration>,
+    denoms: Vec<String>,
     height: u64,
 ) -> Result<(), ContractError> {
     let admin_addr = admin
         .map(|admin| deps.api.addr_validate(&admin))
         .transpose()?;
     ADMIN.set(deps.branch(), admin_addr)?;
+    CONFIG.save(deps.storage, &Config { mode, lock })?;
+    update_denoms(deps.branch(), denoms, vec![])?;
 
     let mut total = 0u64;
     for member in members.into_iter() {
@@ -79,16 +110,56 @@ pub fn execute(
         ExecuteMsg::UpdateMembers { add, remove } => {
             execute_update_members(deps, env, info, add, remove)
         }
-        ExecuteMsg::AddHook { addr } => {
-            Ok(HOOKS.execute_add_hook(&ADMIN, deps, info, api.addr_validate(&addr)?)?)
+        ExecuteMsg::AddHook { addr } => execute_add_hook(deps, info, &HOOKS, addr),
+        ExecuteMsg::RemoveHook { addr } => execute_remove_hook(deps, info, &HOOKS, addr),
+        ExecuteMsg::AddDistributionHook { addr } => {
+            execute_add_hook(deps, info, &DISTRIBUTION_HOOKS, addr)
+        }
+        ExecuteMsg::RemoveDistributionHook { addr } => {
+            execute_remove_hook(deps, info, &DISTRIBUTION_HOOKS, addr)
         }
-        ExecuteMsg::RemoveHook { addr } => {
-            Ok(HOOKS.execute_remove_hook(&ADMIN, deps, info, api.addr_validate(&addr)?)?)
+        ExecuteMsg::Distribute { denoms } => execute_distribute(deps, env, info, denoms),
+        ExecuteMsg::Withdraw { denom } => execute_withdraw(deps, info, denom),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
+        ExecuteMsg::ApproveWeightReduction { weight } => {
+            execute_approve_weight_reduction(deps, info, weight)
         }
-        ExecuteMsg::Distribute { funds } => execute_distribute_funds(deps, info, funds),
+        ExecuteMsg::RevokeWeightReduction {} => execute_revoke_weight_reduction(deps, info),
+        ExecuteMsg::SetLock { lock } => execute_set_lock(deps, info, lock),
+        ExecuteMsg::UpdateDenoms { add, remove } => execute_update_denoms(deps, info, add, remove),
     }
 }
 
+pub fn execute_add_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    hooks: &Hooks,
+    addr: String,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let addr = deps.api.addr_validate(&addr)?;
+    hooks.add_hook(deps.storage, addr.clone())?;
+    Ok(Response::new()
+        .add_attribute("action", "add_hook")
+        .add_attribute("hook", addr)
+        .add_attribute("sender", info.sender))
+}
+
+pub fn execute_remove_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    hooks: &Hooks,
+    addr: String,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+    let addr = deps.api.addr_validate(&addr)?;
+    hooks.remove_hook(deps.storage, addr.clone())?;
+    Ok(Response::new()
+        .add_attribute("action", "remove_hook")
+        .add_attribute("hook", addr)
+        .add_attribute("sender", info.sender))
+}
+
 pub fn execute_update_members(
     mut deps: DepsMut,
     env: Env,
@@ -103,11 +174,19 @@ pub fn execute_update_members(
         attr("sender", &info.sender),
     ];
 
+    let config = CONFIG.load(deps.storage)?;
+    if config
+        .lock
+        .map_or(false, |lock| lock.is_expired(&env.block))
+    {
+        return Err(ContractError::Locked {});
+    }
+
     // make the local update
     let diff = update_members(deps.branch(), env.block.height, info.sender, add, remove)?;
     // call all registered hooks
-    let messages = HOOKS.prepare_hooks(deps.storage, |h| {
-        diff.clone().into_cosmos_msg(h).map(SubMsg::new)
+    let messages = HOOKS.prepare_hooks(deps.storage, MEMBER_HOOK_REPLY_ID, |h| {
+        diff.clone().into_cosmos_msg(h)
     })?;
     Ok(Response::new()
         .add_submessages(messages)
@@ -130,6 +209,10 @@ pub fn update_members(
     // add all new members and update total
     for add in to_add.into_iter() {
         let add_addr = deps.api.addr_validate(&add.addr)?;
+        // credit rewards earned at the old weight before it changes
+        let old = MEMBERS.may_load(deps.storage, &add_addr)?;
+        use_reduction_approval(deps.storage, &add_addr, old, add.weight)?;
+        settle_member(deps.storage, &add_addr, old.unwrap_or_default())?;
         MEMBERS.update(deps.storage, &add_addr, height, |old| -> StdResult<_> {
             total -= old.unwrap_or_default();
             total += add.weight;
@@ -143,6 +226,8 @@ pub fn update_members(
         let old = MEMBERS.may_load(deps.storage, &remove_addr)?;
         // Only process this if they were actually in the list before
         if let Some(weight) = old {
+            use_reduction_approval(deps.storage, &remove_addr, old, 0)?;
+            settle_member(deps.storage, &remove_addr, weight)?;
             diffs.push(MemberDiff::new(remove, Some(weight), None));
             total -= weight;
             MEMBERS.remove(deps.storage, &remove_addr, height)?;
@@ -153,39 +238,514 @@ pub fn update_members(
     Ok(MemberChangedHookMsg { diffs })
 }
 
-pub fn execute_distribute_funds(
+/// Weight reductions need the member's approval for exactly the new weight,
+/// which is removed once it is applied
+fn use_reduction_approval(
+    storage: &mut dyn Storage,
+    addr: &Addr,
+    old: Option<u64>,
+    new: u64,
+) -> Result<(), ContractError> {
+    if old.unwrap_or_default() <= new {
+        return Ok(());
+    }
+    match REDUCTION_APPROVALS.may_load(storage, addr)? {
+        Some(weight) if weight == new => {
+            REDUCTION_APPROVALS.remove(storage, addr);
+            Ok(())
+        }
+        _ => Err(ContractError::ReductionNotApproved {
+            member: addr.to_string(),
+        }),
+    }
+}
+
+pub fn execute_approve_weight_reduction(
     deps: DepsMut,
     info: MessageInfo,
-    funds: Coin,
+    weight: u64,
 ) -> Result<Response, ContractError> {
+    if MEMBERS.may_load(deps.storage, &info.sender)?.is_none() {
+        return Err(ContractError::NotMember {});
+    }
+    REDUCTION_APPROVALS.save(deps.storage, &info.sender, &weight)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "approve_weight_reduction")
+        .add_attribute("weight", weight.to_string())
+        .add_attribute("sender", &info.sender))
+}
+
+pub fn execute_revoke_weight_reduction(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    REDUCTION_APPROVALS.remove(deps.storage, &info.sender);
+
+    Ok(Response::new()
+        .add_attribute("action", "revoke_weight_reduction")
+        .add_attribute("sender", &info.sender))
+}
+
+pub fn execute_set_lock(
+    deps: DepsMut,
+    info: MessageInfo,
+    lock: Expiration,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+
+    let mut config = CONFIG.load(deps.storage)?;
+    if config.lock.is_some() {
+        return Err(ContractError::LockAlreadySet {});
+    }
+    config.lock = Some(lock);
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_lock")
+        .add_attribute("lock", lock.to_string())
+        .add_attribute("sender", &info.sender))
+}
+
+pub fn execute_update_denoms(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
+
+    let attributes = vec![
+        attr("action", "update_denoms"),
+        attr("added", add.join(",")),
+        attr("removed", remove.join(",")),
+        attr("sender", &info.sender),
+    ];
+    update_denoms(deps, add, remove)?;
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+fn update_denoms(
+    deps: DepsMut,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<(), ContractError> {
+    for denom in add {
+        validate_denom(deps.api, &denom)?;
+        DENOMS.save(deps.storage, &denom, &true)?;
+    }
+    for denom in remove {
+        DENOMS.remove(deps.storage, &denom);
+    }
+    if tracked_denoms(deps.storage)? > MAX_DENOMS as usize {
+        return Err(ContractError::TooManyDenoms { max: MAX_DENOMS });
+    }
+    Ok(())
+}
+
+fn validate_denom(api: &dyn Api, denom: &str) -> StdResult<()> {
+    if let Some(contract) = denom.strip_prefix(CW20_PREFIX) {
+        api.addr_validate(contract)?;
+    }
+    Ok(())
+}
+
+// Listed denoms and those with a reward index. A removed denom keeps its index,
+// so it still counts until the cap.
+fn tracked_denoms(storage: &dyn Storage) -> StdResult<usize> {
+    let mut denoms = DENOMS
+        .keys(storage, None, None, Order::Ascending)
+        .chain(REWARD_INDEX.keys(storage, None, None, Order::Ascending))
+        .collect::<StdResult<Vec<_>>>()?;
+    denoms.sort();
+    denoms.dedup();
+    Ok(denoms.len())
+}
+
+pub fn execute_distribute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denoms: Vec<String>,
+) -> Result<Response, ContractError> {
+    let denoms = if denoms.is_empty() {
+        info.funds.iter().map(|c| c.denom.clone()).collect()
+    } else {
+        denoms
+    };
+    distribute(deps, env, info.sender, denoms)
+}
+
+pub fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    // the tokens are already in the contract balance, the sending cw20 contract
+    // is the denom and the original sender is the one distributing.
+    // Any contract can call this, so only listed tokens are trusted.
+    let denom = cw20_denom(&info.sender);
+    if !DENOMS.has(deps.storage, &denom) {
+        return Err(ContractError::DenomNotAllowed { denom });
+    }
+    let sender = deps.api.addr_validate(&wrapper.sender)?;
+    distribute(deps, env, sender, vec![denom])
+}
+
+fn distribute(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    mut denoms: Vec<String>,
+) -> Result<Response, ContractError> {
+    // bank sends require sorted, unique denoms
+    denoms.sort();
+    denoms.dedup();
+
     let total_weight = TOTAL.load(deps.storage)?;
+    if total_weight == 0 {
+        return Err(ContractError::NoMembers {});
+    }
 
-    let messages: StdResult<Vec<_>> = MEMBERS
-        .range(deps.storage, None, None, Order::Ascending)
-        .map(|member| {
-            let (key, weight) = member?;
-            let amount = funds
-                .amount
-                .checked_mul(Uint128::from(weight))?
-                .checked_div(Uint128::from(total_weight))?;
-            Ok(BankMsg::Send {
-                to_address: key.to_string(),
-                amount: vec![coin(Uint128::u128(&amount), funds.denom.clone())],
-            })
-        })
-        .collect();
+    // only listed denoms get a reward index, and only listed cw20 contracts
+    // are queried for a balance
+    let mode = CONFIG.load(deps.storage)?.mode;
+    let unlisted = denoms.iter().find(|denom| {
+        (mode == DistributionMode::Pull || denom.starts_with(CW20_PREFIX))
+            && !DENOMS.has(deps.storage, denom)
+    });
+    if let Some(denom) = unlisted {
+        return Err(ContractError::DenomNotAllowed {
+            denom: denom.clone(),
+        });
+    }
+
+    // the balance already includes any funds attached to this message,
+    // as well as the remainder left over from previous distributions
+    let mut available = vec![];
+    for denom in denoms {
+        let balance = query_balance(deps.as_ref(), &env.contract.address, &denom)?;
+        let outstanding = OUTSTANDING
+            .may_load(deps.storage, &denom)?
+            .unwrap_or_default();
+        let amount = balance.saturating_sub(outstanding);
+        if !amount.is_zero() {
+            available.push(Coin { denom, amount });
+        }
+    }
+    if available.is_empty() {
+        return Err(ContractError::NothingToDistribute {});
+    }
+
+    let shares = member_shares(deps.storage, mode, total_weight, &available)?;
+    let (messages, remainders) = match mode {
+        DistributionMode::Push => (
+            send_shares(&shares)?,
+            remainders_after(&available, &shares)?,
+        ),
+        DistributionMode::Pull => {
+            credit_shares(deps.storage, total_weight, &available)?;
+            // the whole amount is credited, rounding dust is carried over by `carry_dust`
+            (vec![], vec![])
+        }
+    };
+
+    // call all registered distribution hooks
+    let hook = DistributionHookMsg {
+        funds: available.clone(),
+        shares,
+    };
+    let hooks =
+        DISTRIBUTION_HOOKS.prepare_hooks(deps.storage, DISTRIBUTION_HOOK_REPLY_ID, |h| {
+            hook.clone().into_cosmos_msg(h)
+        })?;
 
     let attributes = vec![
         attr("action", "distribute_funds"),
-        attr("funds", funds.to_string()),
-        attr("sender", &info.sender),
+        attr("funds", coins_to_string(&available)),
+        attr("remainder", coins_to_string(&remainders)),
+        attr("sender", &sender),
     ];
 
     Ok(Response::new()
-        .add_messages(messages?)
+        .add_messages(messages)
+        .add_submessages(hooks)
         .add_attributes(attributes))
 }
 
+/// Each member's share of `available` by weight. In `Pull` mode this is the amount
+/// credited through the reward index.
+fn member_shares(
+    storage: &dyn Storage,
+    mode: DistributionMode,
+    total_weight: u64,
+    available: &[Coin],
+) -> StdResult<Vec<MemberShare>> {
+    let mut shares = vec![];
+    for member in MEMBERS.range(storage, None, None, Order::Ascending) {
+        let (addr, weight) = member?;
+        let mut amount = vec![];
+        for funds in available {
+            let share = match mode {
+                DistributionMode::Push => funds
+                    .amount
+                    .checked_mul(Uint128::from(weight))?
+                    .checked_div(Uint128::from(total_weight))?,
+                DistributionMode::Pull => {
+                    Uint128::from(weight) * Decimal::from_ratio(funds.amount, total_weight)
+                }
+            };
+            if !share.is_zero() {
+                amount.push(Coin {
+                    denom: funds.denom.clone(),
+                    amount: share,
+                });
+            }
+        }
+        if !amount.is_empty() {
+            shares.push(MemberShare {
+                addr: addr.to_string(),
+                amount,
+            });
+        }
+    }
+    Ok(shares)
+}
+
+/// What is left of `available` after paying out `shares`
+fn remainders_after(available: &[Coin], shares: &[MemberShare]) -> StdResult<Vec<Coin>> {
+    let mut remainders = available.to_vec();
+    for share in shares.iter().flat_map(|share| share.amount.iter()) {
+        if let Some(remainder) = remainders.iter_mut().find(|r| r.denom == share.denom) {
+            remainder.amount = remainder.amount.checked_sub(share.amount)?;
+        }
+    }
+    Ok(remainders)
+}
+
+fn cw20_denom(contract: &Addr) -> String {
+    format!("{}{}", CW20_PREFIX, contract)
+}
+
+fn query_balance(deps: Deps, addr: &Addr, denom: &str) -> StdResult<Uint128> {
+    match denom.strip_prefix(CW20_PREFIX) {
+        Some(contract) => {
+            let res: BalanceResponse = deps.querier.query_wasm_smart(
+                contract,
+                &Cw20QueryMsg::Balance {
+                    address: addr.to_string(),
+                },
+            )?;
+            Ok(res.balance)
+        }
+        None => Ok(deps.querier.query_balance(addr, denom)?.amount),
+    }
+}
+
+/// Builds the message paying `amount` of a native or CW20 `denom` to `recipient`
+fn payout_msg(recipient: &Addr, denom: &str, amount: Uint128) -> StdResult<CosmosMsg> {
+    Ok(match denom.strip_prefix(CW20_PREFIX) {
+        Some(contract) => WasmMsg::Execute {
+            contract_addr: contract.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+        None => BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![Coin {
+                denom: denom.to_string(),
+                amount,
+            }],
+        }
+        .into(),
+    })
+}
+
+/// Sends each member their share. Native shares are batched into one bank send per member.
+fn send_shares(shares: &[MemberShare]) -> StdResult<Vec<CosmosMsg>> {
+    let mut messages = vec![];
+    for share in shares {
+        let addr = Addr::unchecked(&share.addr);
+        let (cw20, native): (Vec<_>, Vec<_>) = share
+            .amount
+            .iter()
+            .cloned()
+            .partition(|coin| coin.denom.starts_with(CW20_PREFIX));
+        if !native.is_empty() {
+            messages.push(
+                BankMsg::Send {
+                    to_address: addr.to_string(),
+                    amount: native,
+                }
+                .into(),
+            );
+        }
+        for coin in cw20 {
+            messages.push(payout_msg(&addr, &coin.denom, coin.amount)?);
+        }
+    }
+    Ok(messages)
+}
+
+/// Credits `available` to all members by raising the reward index of each denom.
+/// The whole amount is marked outstanding so rounding can never over-credit members.
+fn credit_shares(
+    storage: &mut dyn Storage,
+    total_weight: u64,
+    available: &[Coin],
+) -> StdResult<()> {
+    for funds in available {
+        let delta = Decimal::from_ratio(funds.amount, total_weight);
+        REWARD_INDEX.update(storage, &funds.denom, |index| -> StdResult<_> {
+            Ok(index.unwrap_or_default() + delta)
+        })?;
+        OUTSTANDING.update(storage, &funds.denom, |outstanding| -> StdResult<_> {
+            Ok(outstanding.unwrap_or_default().checked_add(funds.amount)?)
+        })?;
+        // the index is rounded down, so members are credited slightly less than the amount
+        let credited = Decimal::from_ratio(total_weight, 1u128) * delta;
+        carry_dust(
+            storage,
+            &funds.denom,
+            Decimal::from_ratio(funds.amount, 1u128) - credited,
+        )?;
+    }
+    Ok(())
+}
+
+/// Adds `dust` of `denom` to `ROUNDING_DUST` and releases every whole unit of it
+/// from `OUTSTANDING`, so rounding never locks funds in the contract.
+fn carry_dust(storage: &mut dyn Storage, denom: &str, dust: Decimal) -> StdResult<()> {
+    let dust = ROUNDING_DUST.may_load(storage, denom)?.unwrap_or_default() + dust;
+    let whole = Uint128::new(1) * dust;
+    if !whole.is_zero() {
+        OUTSTANDING.update(storage, denom, |outstanding| -> StdResult<_> {
+            Ok(outstanding.unwrap_or_default().checked_sub(whole)?)
+        })?;
+    }
+    ROUNDING_DUST.save(storage, denom, &(dust - Decimal::from_ratio(whole, 1u128)))
+}
+
+/// Moves everything a member earned since they were last settled into `CLAIMABLE`.
+/// Must be called with the member's weight before it changes.
+fn settle_member(storage: &mut dyn Storage, addr: &Addr, weight: u64) -> StdResult<()> {
+    let denoms = REWARD_INDEX
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for denom in denoms {
+        settle_denom(storage, addr, weight, &denom)?;
+    }
+    Ok(())
+}
+
+/// Settles a single denom of a member, enough when the weight stays the same
+fn settle_denom(storage: &mut dyn Storage, addr: &Addr, weight: u64, denom: &str) -> StdResult<()> {
+    let index = match REWARD_INDEX.may_load(storage, denom)? {
+        Some(index) => index,
+        None => return Ok(()),
+    };
+    let last = MEMBER_INDEX
+        .may_load(storage, (addr, denom))?
+        .unwrap_or_default();
+    let accrued = Uint128::from(weight) * (index - last);
+    if !accrued.is_zero() {
+        CLAIMABLE.update(storage, (addr, denom), |claimable| -> StdResult<_> {
+            Ok(claimable.unwrap_or_default().checked_add(accrued)?)
+        })?;
+    }
+    // the fraction rounded off the member's share
+    let earned = Decimal::from_ratio(weight, 1u128) * (index - last);
+    carry_dust(storage, denom, earned - Decimal::from_ratio(accrued, 1u128))?;
+    MEMBER_INDEX.save(storage, (addr, denom), &index)
+}
+
+fn accrued(
+    storage: &dyn Storage,
+    addr: &Addr,
+    denom: &str,
+    index: Decimal,
+    weight: u64,
+) -> StdResult<Uint128> {
+    let last = MEMBER_INDEX
+        .may_load(storage, (addr, denom))?
+        .unwrap_or_default();
+    Ok(Uint128::from(weight) * (index - last))
+}
+
+pub fn execute_withdraw(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+) -> Result<Response, ContractError> {
+    let weight = MEMBERS
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    settle_denom(deps.storage, &info.sender, weight, &denom)?;
+
+    let amount = CLAIMABLE
+        .may_load(deps.storage, (&info.sender, &denom))?
+        .unwrap_or_default();
+    if amount.is_zero() {
+        return Err(ContractError::NothingToWithdraw {});
+    }
+    CLAIMABLE.remove(deps.storage, (&info.sender, &denom));
+    OUTSTANDING.update(deps.storage, &denom, |outstanding| -> StdResult<_> {
+        Ok(outstanding.unwrap_or_default().checked_sub(amount)?)
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "withdraw")
+        .add_attribute("funds", format!("{}{}", amount, denom))
+        .add_attribute("sender", &info.sender)
+        .add_message(payout_msg(&info.sender, &denom, amount)?))
+}
+
+fn coins_to_string(coins: &[Coin]) -> String {
+    coins
+        .iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// A failing hook is skipped so it can't block distributions or member updates
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let action = match msg.id {
+        DISTRIBUTION_HOOK_REPLY_ID => "distribution_hook_failed",
+        MEMBER_HOOK_REPLY_ID => "member_hook_failed",
+        id => return Err(ContractError::UnknownReplyId { id }),
+    };
+    Ok(match msg.result {
+        SubMsgResult::Ok(_) => Response::new(),
+        SubMsgResult::Err(err) => Response::new()
+            .add_attribute("action", action)
+            .add_attribute("error", err),
+    })
+}
+
+/// Moves the hooks of contracts instantiated before they were stored in a map,
+/// and lists the denoms already credited so they can still be distributed
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    HOOKS.migrate_from_item(deps.storage)?;
+    DISTRIBUTION_HOOKS.migrate_from_item(deps.storage)?;
+    let credited = REWARD_INDEX
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for denom in credited {
+        DENOMS.save(deps.storage, &denom, &true)?;
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::new().add_attribute("action", "migrate"))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -199,9 +759,61 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::TotalWeight {} => to_binary(&query_total_weight(deps)?),
         QueryMsg::Admin {} => to_binary(&ADMIN.query_admin(deps)?),
         QueryMsg::Hooks {} => to_binary(&HOOKS.query_hooks(deps)?),
+        QueryMsg::DistributionHooks {} => to_binary(&DISTRIBUTION_HOOKS.query_hooks(deps)?),
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::Claimable { addr } => to_binary(&query_claimable(deps, addr)?),
+        QueryMsg::Denoms {} => to_binary(&query_denoms(deps)?),
+        QueryMsg::WeightReduction { addr } => to_binary(&query_weight_reduction(deps, addr)?),
+        QueryMsg::History {
+            addr,
+            start_after,
+            limit,
+        } => to_binary(&query_history(deps, addr, start_after, limit)?),
     }
 }
 
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse {
+        mode: config.mode,
+        lock: config.lock,
+    })
+}
+
+fn query_denoms(deps: Deps) -> StdResult<DenomsResponse> {
+    let denoms = DENOMS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+    Ok(DenomsResponse { denoms })
+}
+
+fn query_weight_reduction(deps: Deps, addr: String) -> StdResult<WeightReductionResponse> {
+    let addr = deps.api.addr_validate(&addr)?;
+    let weight = REDUCTION_APPROVALS.may_load(deps.storage, &addr)?;
+    Ok(WeightReductionResponse { weight })
+}
+
+fn query_claimable(deps: Deps, addr: String) -> StdResult<ClaimableResponse> {
+    let addr = deps.api.addr_validate(&addr)?;
+    let weight = MEMBERS.may_load(deps.storage, &addr)?.unwrap_or_default();
+
+    let claimable = REWARD_INDEX
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (denom, index) = item?;
+            let settled = CLAIMABLE
+                .may_load(deps.storage, (&addr, &denom))?
+                .unwrap_or_default();
+            let amount =
+                settled.checked_add(accrued(deps.storage, &addr, &denom, index, weight)?)?;
+            Ok(Coin { denom, amount })
+        })
+        .filter(|coin| !matches!(coin, Ok(c) if c.amount.is_zero()))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(ClaimableResponse { claimable })
+}
+
 fn query_total_weight(deps: Deps) -> StdResult<TotalWeightResponse> {
     let weight = TOTAL.load(deps.storage)?;
     Ok(TotalWeightResponse { weight })
@@ -244,13 +856,66 @@ fn list_members(
     Ok(MemberListResponse { members: members? })
 }
 
+fn query_history(
+    deps: Deps,
+    addr: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<HistoryResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let addr = deps.api.addr_validate(&addr)?;
+    let start = start_after.map(Bound::exclusive);
+
+    // the changelog stores the weight from before each change, so the weight after
+    // a change is the old weight of the next one, or the current weight for the last
+    let mut changelog = MEMBERS
+        .changelog()
+        .prefix(&addr)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+    let last = if changelog.len() > limit {
+        changelog.pop().map(|(_, next)| next.old)
+    } else {
+        None
+    };
+    let new_weights = changelog
+        .iter()
+        .skip(1)
+        .map(|(_, next)| -> StdResult<_> { Ok(next.old) })
+        .chain(std::iter::once(match last {
+            Some(old) => Ok(old),
+            None => MEMBERS.may_load(deps.storage, &addr),
+        }));
+
+    let changes = changelog
+        .iter()
+        .zip(new_weights)
+        .map(|((height, change), new)| {
+            Ok(WeightChange {
+                height: *height,
+                old: change.old,
+                new: new?,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(HistoryResponse { changes })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{from_slice, Api, CosmosMsg, OwnedDeps, Querier, Storage};
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_dependencies_with_balance, mock_env, mock_info, MOCK_CONTRACT_ADDR,
+    };
+    use cosmwasm_std::{
+        coin, coins, from_slice, Api, ContractResult, CosmosMsg, OwnedDeps, Querier, Storage,
+        SubMsg, SystemError, SystemResult, WasmQuery,
+    };
     use cw4::{member_key, TOTAL_KEY};
-    use cw_controllers::{AdminError, HookError};
+    use cw_controllers::AdminError;
+    use sg_controllers::HookError;
     use sg_std::NATIVE_DENOM;
 
     const INIT_ADMIN: &str = "juan";
@@ -271,11 +936,19 @@ mod tests {
                     weight: 6,
                 },
             ],
+            mode: None,
+            lock: None,
+            denoms: vec![],
         };
         let info = mock_info("creator", &[]);
         instantiate(deps, mock_env(), info, msg).unwrap();
     }
 
+    fn approve_reduction(deps: DepsMut, member: &str, weight: u64) {
+        let msg = ExecuteMsg::ApproveWeightReduction { weight };
+        execute(deps, mock_env(), mock_info(member, &[]), msg).unwrap();
+    }
+
     #[test]
     fn proper_instantiation() {
         let mut deps = mock_dependencies();
@@ -371,7 +1044,24 @@ mod tests {
         // This will get us the values at the start of the block after instantiate (expected initial values)
         assert_users(&deps, Some(11), Some(6), None, Some(height + 1));
 
+        // USER1 did not approve being removed
+        let err = update_members(
+            deps.as_mut(),
+            height + 10,
+            Addr::unchecked(INIT_ADMIN),
+            add.clone(),
+            remove.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ReductionNotApproved {
+                member: USER1.into()
+            }
+        );
+
         // admin updates properly
+        approve_reduction(deps.as_mut(), USER1, 0);
         update_members(
             deps.as_mut(),
             height + 10,
@@ -402,6 +1092,7 @@ mod tests {
         let remove = vec![USER3.into()];
 
         // admin updates properly
+        approve_reduction(deps.as_mut(), USER1, 4);
         let height = mock_env().block.height;
         update_members(
             deps.as_mut(),
@@ -434,6 +1125,7 @@ mod tests {
         let remove = vec![USER1.into()];
 
         // admin updates properly
+        approve_reduction(deps.as_mut(), USER1, 0);
         let height = mock_env().block.height;
         update_members(
             deps.as_mut(),
@@ -471,7 +1163,7 @@ mod tests {
             add_msg.clone(),
         )
         .unwrap_err();
-        assert_eq!(err, HookError::Admin(AdminError::NotAdmin {}).into());
+        assert_eq!(err, AdminError::NotAdmin {}.into());
 
         // admin can add it, and it appears in the query
         let admin_info = mock_info(INIT_ADMIN, &[]);
@@ -507,7 +1199,7 @@ mod tests {
         // non-admin cannot remove
         let remove_msg = ExecuteMsg::RemoveHook { addr: contract1 };
         let err = execute(deps.as_mut(), mock_env(), user_info, remove_msg.clone()).unwrap_err();
-        assert_eq!(err, HookError::Admin(AdminError::NotAdmin {}).into());
+        assert_eq!(err, AdminError::NotAdmin {}.into());
 
         // remove the original
         let _ = execute(deps.as_mut(), mock_env(), admin_info, remove_msg).unwrap();
@@ -515,6 +1207,23 @@ mod tests {
         assert_eq!(hooks.hooks, vec![contract2]);
     }
 
+    #[test]
+    fn migrate_moves_hooks() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+
+        // earlier versions kept all hooks in one item under the same key
+        let legacy: cw_storage_plus::Item<Vec<Addr>> =
+            cw_storage_plus::Item::new("royalty-group-hooks");
+        legacy
+            .save(deps.as_mut().storage, &vec![Addr::unchecked("hook1")])
+            .unwrap();
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        let hooks = HOOKS.query_hooks(deps.as_ref()).unwrap();
+        assert_eq!(hooks.hooks, vec!["hook1".to_string()]);
+    }
+
     #[test]
     fn hooks_fire() {
         let mut deps = mock_dependencies();
@@ -554,6 +1263,7 @@ mod tests {
         let msg = ExecuteMsg::UpdateMembers { remove, add };
 
         // admin updates properly
+        approve_reduction(deps.as_mut(), USER2, 0);
         assert_users(&deps, Some(11), Some(6), None, None);
         let res = execute(deps.as_mut(), mock_env(), admin_info, msg).unwrap();
         assert_users(&deps, Some(20), None, Some(5), None);
@@ -567,8 +1277,14 @@ mod tests {
             MemberDiff::new(USER2, Some(6), None),
         ];
         let hook_msg = MemberChangedHookMsg { diffs };
-        let msg1 = SubMsg::new(hook_msg.clone().into_cosmos_msg(contract1).unwrap());
-        let msg2 = SubMsg::new(hook_msg.into_cosmos_msg(contract2).unwrap());
+        let msg1 = SubMsg::reply_on_error(
+            hook_msg.clone().into_cosmos_msg(contract1).unwrap(),
+            MEMBER_HOOK_REPLY_ID,
+        );
+        let msg2 = SubMsg::reply_on_error(
+            hook_msg.into_cosmos_msg(contract2).unwrap(),
+            MEMBER_HOOK_REPLY_ID,
+        );
         assert_eq!(res.messages, vec![msg1, msg2]);
     }
 
@@ -595,12 +1311,13 @@ mod tests {
 
     #[test]
     fn check_distribute_funds() {
-        let mut deps = mock_dependencies();
+        let mut deps = mock_dependencies_with_balance(&coins(123u128, NATIVE_DENOM));
         do_instantiate(deps.as_mut());
 
-        let funds = coin(123u128, NATIVE_DENOM);
         let admin_info = mock_info(INIT_ADMIN, &[]);
-        let msg = ExecuteMsg::Distribute { funds };
+        let msg = ExecuteMsg::Distribute {
+            denoms: vec![NATIVE_DENOM.into()],
+        };
         let result = execute(deps.as_mut(), mock_env(), admin_info, msg).unwrap();
 
         assert_users(&deps, Some(11), Some(6), None, None);
@@ -632,5 +1349,554 @@ mod tests {
             },
             bank_send_2
         );
+
+        // the rounding dust is carried forward
+        assert_eq!(
+            result.attributes[2],
+            attr("remainder", coin(1u128, NATIVE_DENOM).to_string())
+        );
+    }
+
+    #[test]
+    fn distribute_multiple_denoms() {
+        let mut deps =
+            mock_dependencies_with_balance(&[coin(34u128, "uatom"), coin(170u128, NATIVE_DENOM)]);
+        do_instantiate(deps.as_mut());
+
+        // attached funds are used when no denoms are given
+        let info = mock_info(USER3, &[coin(170u128, NATIVE_DENOM), coin(34u128, "uatom")]);
+        let msg = ExecuteMsg::Distribute { denoms: vec![] };
+        let result = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            result.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: USER2.into(),
+                    amount: vec![coin(12u128, "uatom"), coin(60u128, NATIVE_DENOM)],
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: USER1.into(),
+                    amount: vec![coin(22u128, "uatom"), coin(110u128, NATIVE_DENOM)],
+                }),
+            ]
+        );
+
+        // nothing to distribute
+        let info = mock_info(USER3, &[]);
+        let msg = ExecuteMsg::Distribute {
+            denoms: vec!["uosmo".into()],
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::NothingToDistribute {});
+
+        let msg = ExecuteMsg::Distribute { denoms: vec![] };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::NothingToDistribute {});
+    }
+
+    #[test]
+    fn pull_mode_withdrawals() {
+        let mut deps = mock_dependencies_with_balance(&coins(170u128, NATIVE_DENOM));
+        let msg = InstantiateMsg {
+            admin: Some(INIT_ADMIN.into()),
+            members: vec![
+                Member {
+                    addr: USER1.into(),
+                    weight: 11,
+                },
+                Member {
+                    addr: USER2.into(),
+                    weight: 6,
+                },
+            ],
+            mode: Some(DistributionMode::Pull),
+            lock: None,
+            denoms: vec![NATIVE_DENOM.into()],
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // funds are credited instead of sent
+        let info = mock_info(USER3, &[]);
+        let msg = ExecuteMsg::Distribute {
+            denoms: vec![NATIVE_DENOM.into()],
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+        assert!(res.messages.is_empty());
+        let res = query_claimable(deps.as_ref(), USER1.into()).unwrap();
+        assert_eq!(res.claimable, coins(110u128, NATIVE_DENOM));
+
+        // credited funds are not distributed again
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::NothingToDistribute {});
+
+        // USER1 keeps what they earned after being removed
+        approve_reduction(deps.as_mut(), USER1, 0);
+        let add = vec![Member {
+            addr: USER3.into(),
+            weight: 5,
+        }];
+        let update = ExecuteMsg::UpdateMembers {
+            remove: vec![USER1.into()],
+            add,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(INIT_ADMIN, &[]),
+            update,
+        )
+        .unwrap();
+
+        // the next distribution only counts the new weights
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, coins(280u128, NATIVE_DENOM));
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        for (user, amount) in [(USER1, 110u128), (USER2, 120u128), (USER3, 50u128)] {
+            let res = query_claimable(deps.as_ref(), user.into()).unwrap();
+            assert_eq!(res.claimable, coins(amount, NATIVE_DENOM));
+        }
+
+        let withdraw = ExecuteMsg::Withdraw {
+            denom: NATIVE_DENOM.into(),
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(USER1, &[]),
+            withdraw.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: USER1.into(),
+                amount: coins(110u128, NATIVE_DENOM),
+            })]
+        );
+        let res = query_claimable(deps.as_ref(), USER1.into()).unwrap();
+        assert!(res.claimable.is_empty());
+
+        let err = execute(deps.as_mut(), mock_env(), mock_info(USER1, &[]), withdraw).unwrap_err();
+        assert_eq!(err, ContractError::NothingToWithdraw {});
+    }
+
+    #[test]
+    fn pull_mode_carries_rounding_dust() {
+        let mut deps = mock_dependencies_with_balance(&coins(10u128, NATIVE_DENOM));
+        let msg = InstantiateMsg {
+            admin: Some(INIT_ADMIN.into()),
+            members: [USER1, USER2, USER3]
+                .iter()
+                .map(|addr| Member {
+                    addr: addr.to_string(),
+                    weight: 1,
+                })
+                .collect(),
+            mode: Some(DistributionMode::Pull),
+            lock: None,
+            denoms: vec![NATIVE_DENOM.into()],
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::Distribute {
+            denoms: vec![NATIVE_DENOM.into()],
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(INIT_ADMIN, &[]),
+            msg.clone(),
+        )
+        .unwrap();
+
+        // each member gets 3 and the last unit is split three ways
+        let withdraw = ExecuteMsg::Withdraw {
+            denom: NATIVE_DENOM.into(),
+        };
+        for (user, balance) in [(USER1, 7u128), (USER2, 4u128), (USER3, 1u128)] {
+            let res = query_claimable(deps.as_ref(), user.into()).unwrap();
+            assert_eq!(res.claimable, coins(3u128, NATIVE_DENOM));
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(user, &[]),
+                withdraw.clone(),
+            )
+            .unwrap();
+            deps.querier
+                .update_balance(MOCK_CONTRACT_ADDR, coins(balance, NATIVE_DENOM));
+
+            // the unit is only released once every share of it is rounded off
+            let res = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(INIT_ADMIN, &[]),
+                msg.clone(),
+            );
+            if user == USER3 {
+                let res = res.unwrap();
+                assert!(res
+                    .attributes
+                    .contains(&attr("funds", coins_to_string(&coins(1u128, NATIVE_DENOM)))));
+            } else {
+                assert_eq!(res.unwrap_err(), ContractError::NothingToDistribute {});
+            }
+        }
+        assert_eq!(
+            OUTSTANDING.load(&deps.storage, NATIVE_DENOM).unwrap(),
+            Uint128::new(1)
+        );
+    }
+
+    #[test]
+    fn pull_mode_denom_allowlist() {
+        let mut deps =
+            mock_dependencies_with_balance(&[coin(100u128, NATIVE_DENOM), coin(100u128, "uatom")]);
+        let msg = InstantiateMsg {
+            admin: Some(INIT_ADMIN.into()),
+            members: vec![
+                Member {
+                    addr: USER1.into(),
+                    weight: 1,
+                },
+                Member {
+                    addr: USER2.into(),
+                    weight: 1,
+                },
+            ],
+            mode: Some(DistributionMode::Pull),
+            lock: None,
+            denoms: vec![NATIVE_DENOM.into()],
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // unlisted denoms get no reward index
+        let distribute = ExecuteMsg::Distribute {
+            denoms: vec![NATIVE_DENOM.into(), "uatom".into()],
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(USER3, &[]),
+            distribute.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::DenomNotAllowed {
+                denom: "uatom".into()
+            }
+        );
+
+        // only the admin can list denoms
+        let update = ExecuteMsg::UpdateDenoms {
+            add: vec!["uatom".into()],
+            remove: vec![],
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(USER1, &[]),
+            update.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, AdminError::NotAdmin {}.into());
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(INIT_ADMIN, &[]),
+            update,
+        )
+        .unwrap();
+        assert_eq!(
+            query_denoms(deps.as_ref()).unwrap().denoms,
+            vec!["uatom".to_string(), NATIVE_DENOM.to_string()]
+        );
+        execute(deps.as_mut(), mock_env(), mock_info(USER3, &[]), distribute).unwrap();
+
+        // a withdrawal only settles the requested denom
+        let withdraw = ExecuteMsg::Withdraw {
+            denom: NATIVE_DENOM.into(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(USER1, &[]), withdraw).unwrap();
+        let user1 = Addr::unchecked(USER1);
+        assert!(MEMBER_INDEX
+            .may_load(&deps.storage, (&user1, NATIVE_DENOM))
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            MEMBER_INDEX
+                .may_load(&deps.storage, (&user1, "uatom"))
+                .unwrap(),
+            None
+        );
+        let res = query_claimable(deps.as_ref(), USER1.into()).unwrap();
+        assert_eq!(res.claimable, coins(50u128, "uatom"));
+
+        // removed denoms keep their index and still count towards the cap
+        let extra: Vec<String> = (0..MAX_DENOMS - 2).map(|i| format!("denom{}", i)).collect();
+        let update = ExecuteMsg::UpdateDenoms {
+            add: extra,
+            remove: vec!["uatom".into()],
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(INIT_ADMIN, &[]),
+            update,
+        )
+        .unwrap();
+        let update = ExecuteMsg::UpdateDenoms {
+            add: vec!["one_more".into()],
+            remove: vec![],
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(INIT_ADMIN, &[]),
+            update,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::TooManyDenoms { max: MAX_DENOMS });
+    }
+
+    #[test]
+    fn distribute_cw20() {
+        const CW20: &str = "cw20token";
+
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+        let update = ExecuteMsg::UpdateDenoms {
+            add: vec![cw20_denom(&Addr::unchecked(CW20))],
+            remove: vec![],
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(INIT_ADMIN, &[]),
+            update,
+        )
+        .unwrap();
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { contract_addr, .. } if contract_addr == CW20 => {
+                let res = BalanceResponse {
+                    balance: Uint128::new(123),
+                };
+                SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "wasm".into(),
+            }),
+        });
+
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: USER3.into(),
+            amount: Uint128::new(123),
+            msg: Binary::default(),
+        });
+
+        // unlisted contracts can't report a balance to distribute
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("fake_cw20", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::DenomNotAllowed {
+                denom: format!("{}fake_cw20", CW20_PREFIX)
+            }
+        );
+        let distribute = ExecuteMsg::Distribute {
+            denoms: vec![format!("{}fake_cw20", CW20_PREFIX)],
+        };
+        let err =
+            execute(deps.as_mut(), mock_env(), mock_info(USER3, &[]), distribute).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::DenomNotAllowed {
+                denom: format!("{}fake_cw20", CW20_PREFIX)
+            }
+        );
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info(CW20, &[]), msg).unwrap();
+
+        let transfer = |recipient: &str, amount: u128| {
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: CW20.into(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: recipient.into(),
+                    amount: Uint128::new(amount),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        };
+        assert_eq!(res.messages, vec![transfer(USER2, 43), transfer(USER1, 79)]);
+        assert_eq!(
+            res.attributes[2],
+            attr("remainder", format!("1{}{}", CW20_PREFIX, CW20))
+        );
+    }
+
+    #[test]
+    fn weight_history_and_lock() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+        let admin_info = mock_info(INIT_ADMIN, &[]);
+        let mut env = mock_env();
+        let height = env.block.height;
+
+        let update = |weight: u64| ExecuteMsg::UpdateMembers {
+            remove: vec![],
+            add: vec![Member {
+                addr: USER1.into(),
+                weight,
+            }],
+        };
+
+        // increases need no approval
+        env.block.height = height + 1;
+        execute(deps.as_mut(), env.clone(), admin_info.clone(), update(20)).unwrap();
+
+        // approvals are used up
+        approve_reduction(deps.as_mut(), USER1, 8);
+        let res = query_weight_reduction(deps.as_ref(), USER1.into()).unwrap();
+        assert_eq!(res.weight, Some(8));
+        env.block.height = height + 2;
+        execute(deps.as_mut(), env.clone(), admin_info.clone(), update(8)).unwrap();
+        let res = query_weight_reduction(deps.as_ref(), USER1.into()).unwrap();
+        assert_eq!(res.weight, None);
+
+        let res = query_history(deps.as_ref(), USER1.into(), None, None).unwrap();
+        assert_eq!(
+            res.changes,
+            vec![
+                WeightChange {
+                    height,
+                    old: None,
+                    new: Some(11),
+                },
+                WeightChange {
+                    height: height + 1,
+                    old: Some(11),
+                    new: Some(20),
+                },
+                WeightChange {
+                    height: height + 2,
+                    old: Some(20),
+                    new: Some(8),
+                },
+            ]
+        );
+        let res = query_history(deps.as_ref(), USER1.into(), Some(height), Some(1)).unwrap();
+        assert_eq!(
+            res.changes,
+            vec![WeightChange {
+                height: height + 1,
+                old: Some(11),
+                new: Some(20),
+            }]
+        );
+
+        // only the admin can lock, and only once
+        let lock = Expiration::AtHeight(height + 10);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(USER1, &[]),
+            ExecuteMsg::SetLock { lock },
+        )
+        .unwrap_err();
+        assert_eq!(err, AdminError::NotAdmin {}.into());
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            admin_info.clone(),
+            ExecuteMsg::SetLock { lock },
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            admin_info.clone(),
+            ExecuteMsg::SetLock { lock },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::LockAlreadySet {});
+
+        // weights are immutable once the lock expires
+        env.block.height = height + 10;
+        let err = execute(deps.as_mut(), env, admin_info, update(30)).unwrap_err();
+        assert_eq!(err, ContractError::Locked {});
+    }
+
+    #[test]
+    fn distribution_hooks_fire() {
+        let mut deps = mock_dependencies_with_balance(&coins(123u128, NATIVE_DENOM));
+        do_instantiate(deps.as_mut());
+        let admin_info = mock_info(INIT_ADMIN, &[]);
+
+        // membership and distribution hooks are kept apart
+        let msg = ExecuteMsg::AddHook {
+            addr: "member_hook".into(),
+        };
+        execute(deps.as_mut(), mock_env(), admin_info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::AddDistributionHook {
+            addr: "distribution_hook".into(),
+        };
+        execute(deps.as_mut(), mock_env(), admin_info.clone(), msg).unwrap();
+        let hooks = DISTRIBUTION_HOOKS.query_hooks(deps.as_ref()).unwrap();
+        assert_eq!(hooks.hooks, vec!["distribution_hook".to_string()]);
+
+        let msg = ExecuteMsg::Distribute {
+            denoms: vec![NATIVE_DENOM.into()],
+        };
+        let res = execute(deps.as_mut(), mock_env(), admin_info, msg).unwrap();
+
+        // two bank sends followed by the distribution hook
+        assert_eq!(res.messages.len(), 3);
+        let hook_msg = DistributionHookMsg {
+            funds: coins(123u128, NATIVE_DENOM),
+            shares: vec![
+                MemberShare {
+                    addr: USER2.into(),
+                    amount: coins(43u128, NATIVE_DENOM),
+                },
+                MemberShare {
+                    addr: USER1.into(),
+                    amount: coins(79u128, NATIVE_DENOM),
+                },
+            ],
+        };
+        assert_eq!(
+            res.messages[2],
+            SubMsg::reply_on_error(
+                hook_msg.into_cosmos_msg("distribution_hook").unwrap(),
+                DISTRIBUTION_HOOK_REPLY_ID
+            )
+        );
+
+        // a failing hook doesn't revert the distribution
+        let reply_msg = Reply {
+            id: DISTRIBUTION_HOOK_REPLY_ID,
+            result: SubMsgResult::Err("hook error".into()),
+        };
+        let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "distribution_hook_failed"),
+                attr("error", "hook error")
+            ]
+        );
+        let reply_msg = Reply {
+            id: 42,
+            result: SubMsgResult::Err("hook error".into()),
+        };
+        let err = reply(deps.as_mut(), mock_env(), reply_msg).unwrap_err();
+        assert_eq!(err, ContractError::UnknownReplyId { id: 42 });
     }
 }
@@ -1,7 +1,8 @@
 use cosmwasm_std::StdError;
 use thiserror::Error;
 
-use cw_controllers::{AdminError, HookError};
+use cw_controllers::AdminError;
+use sg_controllers::HookError;
 
 #[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
@@ -16,4 +17,34 @@ pub enum ContractError {
 
     #[error("Unauthorized")]
     Unauthorized {},
+
+    #[error("Group has no weight to distribute to")]
+    NoMembers {},
+
+    #[error("Nothing to distribute")]
+    NothingToDistribute {},
+
+    #[error("Nothing to withdraw")]
+    NothingToWithdraw {},
+
+    #[error("Not a member")]
+    NotMember {},
+
+    #[error("Weight reduction of {member} was not approved")]
+    ReductionNotApproved { member: String },
+
+    #[error("Member weights are locked")]
+    Locked {},
+
+    #[error("Lock is already set")]
+    LockAlreadySet {},
+
+    #[error("Denom {denom} is not allowed")]
+    DenomNotAllowed { denom: String },
+
+    #[error("At most {max} denoms can be tracked")]
+    TooManyDenoms { max: u32 },
+
+    #[error("Unknown reply id {id}")]
+    UnknownReplyId { id: u64 },
 }
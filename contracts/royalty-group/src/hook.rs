@@ -0,0 +1,47 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{to_binary, Binary, Coin, CosmosMsg, StdResult, WasmMsg};
+
+/// DistributionHookMsg should be de/serialized under `DistributionHook()` variant in a ExecuteMsg.
+/// This contains what was distributed and each member's share of it.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct DistributionHookMsg {
+    /// Everything that was distributed. CW20 tokens use a `cw20:<contract>` denom
+    pub funds: Vec<Coin>,
+    /// The share of each member that received something
+    pub shares: Vec<MemberShare>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MemberShare {
+    pub addr: String,
+    pub amount: Vec<Coin>,
+}
+
+impl DistributionHookMsg {
+    /// serializes the message
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let msg = DistributionExecuteMsg::DistributionHook(self);
+        to_binary(&msg)
+    }
+
+    /// creates a cosmos_msg sending this struct to the named contract
+    pub fn into_cosmos_msg<T: Into<String>>(self, contract_addr: T) -> StdResult<CosmosMsg> {
+        let msg = self.into_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
+
+/// This is just a helper to properly serialize the above message.
+/// Subscribing contracts can embed the same variant in their own ExecuteMsg
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DistributionExecuteMsg {
+    DistributionHook(DistributionHookMsg),
+}
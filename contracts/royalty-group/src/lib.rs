@@ -1,6 +1,7 @@
 pub mod contract;
 pub mod error;
 pub mod helpers;
+pub mod hook;
 pub mod msg;
 pub mod state;
 
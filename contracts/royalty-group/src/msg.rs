@@ -1,5 +1,7 @@
 use cosmwasm_std::Coin;
+use cw20::Cw20ReceiveMsg;
 use cw4::Member;
+use cw_utils::Expiration;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -10,6 +12,23 @@ pub struct InstantiateMsg {
     /// Omit it to make the group immutable.
     pub admin: Option<String>,
     pub members: Vec<Member>,
+    /// How distributed funds reach members. Defaults to `Push`.
+    pub mode: Option<DistributionMode>,
+    /// Once this expires member weights can no longer be changed
+    pub lock: Option<Expiration>,
+    /// Denoms that can be distributed in `Pull` mode, CW20 tokens as `cw20:<contract>`.
+    /// CW20 tokens must be listed in either mode
+    #[serde(default)]
+    pub denoms: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DistributionMode {
+    /// Every distribution sends each member their share directly
+    Push,
+    /// Every distribution credits each member's share, which they withdraw themselves
+    Pull,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -27,8 +46,34 @@ pub enum ExecuteMsg {
     AddHook { addr: String },
     /// Remove a hook. Must be called by Admin
     RemoveHook { addr: String },
-    /// Distribute funds to all members based on weight
-    Distribute { funds: Coin },
+    /// Add a new hook to be informed of all distributions. Must be called by Admin
+    AddDistributionHook { addr: String },
+    /// Remove a distribution hook. Must be called by Admin
+    RemoveDistributionHook { addr: String },
+    /// Distribute the contract balance of each denom to all members based on weight.
+    /// If `denoms` is empty, the denoms of the attached funds are used.
+    /// Any rounding remainder stays in the contract for the next distribution.
+    Distribute { denoms: Vec<String> },
+    /// Withdraw everything credited to the sender in `denom`. Only used in `Pull` mode.
+    /// CW20 tokens are withdrawn with a `cw20:<contract>` denom
+    Withdraw { denom: String },
+    /// Distribute received CW20 tokens to all members based on weight
+    Receive(Cw20ReceiveMsg),
+    /// Allow the admin to reduce the sender's weight to `weight` (0 for removal).
+    /// The approval is used up by the next update that applies it
+    ApproveWeightReduction { weight: u64 },
+    /// Withdraw a pending weight reduction approval
+    RevokeWeightReduction {},
+    /// Set the time after which member weights can no longer be changed.
+    /// Must be called by Admin, and only if no lock is set yet
+    SetLock { lock: Expiration },
+    /// Change the denoms that can be distributed in `Pull` mode and the CW20 tokens
+    /// accepted in either mode. Must be called by Admin.
+    /// Removed denoms can still be withdrawn
+    UpdateDenoms {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -50,4 +95,57 @@ pub enum QueryMsg {
     },
     /// Shows all registered hooks. Returns HooksResponse.
     Hooks {},
+    /// Shows all registered distribution hooks. Returns HooksResponse.
+    DistributionHooks {},
+    /// Returns ConfigResponse
+    Config {},
+    /// Funds credited to `addr` that can be withdrawn. Returns ClaimableResponse
+    Claimable { addr: String },
+    /// The weight reduction `addr` has approved. Returns WeightReductionResponse
+    WeightReduction { addr: String },
+    /// Weight changes of `addr` by block height. Returns HistoryResponse
+    History {
+        addr: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Denoms that can be distributed in `Pull` mode and the accepted CW20 tokens.
+    /// Returns DenomsResponse
+    Denoms {},
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ConfigResponse {
+    pub mode: DistributionMode,
+    pub lock: Option<Expiration>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ClaimableResponse {
+    pub claimable: Vec<Coin>,
 }
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct DenomsResponse {
+    pub denoms: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct WeightReductionResponse {
+    pub weight: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct WeightChange {
+    pub height: u64,
+    pub old: Option<u64>,
+    pub new: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct HistoryResponse {
+    pub changes: Vec<WeightChange>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
@@ -1,10 +1,27 @@
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Decimal, Uint128};
 use cw4::TOTAL_KEY;
-use cw_controllers::{Admin, Hooks};
-use cw_storage_plus::{Item, SnapshotMap, Strategy};
+use cw_controllers::Admin;
+use cw_storage_plus::{Item, Map, SnapshotMap, Strategy};
+use cw_utils::Expiration;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sg_controllers::Hooks;
+
+use crate::msg::DistributionMode;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub mode: DistributionMode,
+    pub lock: Option<Expiration>,
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
 
 pub const ADMIN: Admin = Admin::new("admin");
+/// Informed of membership changes
 pub const HOOKS: Hooks = Hooks::new("royalty-group-hooks");
+/// Informed of every distribution
+pub const DISTRIBUTION_HOOKS: Hooks = Hooks::new("royalty-group-distribution-hooks");
 
 pub const TOTAL: Item<u64> = Item::new(TOTAL_KEY);
 
@@ -14,3 +31,22 @@ pub const MEMBERS: SnapshotMap<&Addr, u64> = SnapshotMap::new(
     cw4::MEMBERS_CHANGELOG,
     Strategy::EveryBlock,
 );
+
+/// Denoms that can be credited in `Pull` mode. A denom gets a reward index only once listed.
+/// CW20 tokens are accepted only if listed, in either mode.
+pub const DENOMS: Map<&str, bool> = Map::new("denoms");
+/// Cumulative amount of each denom credited per unit of weight
+pub const REWARD_INDEX: Map<&str, Decimal> = Map::new("reward_index");
+/// The reward index of each (member, denom) at the time the member was last settled
+pub const MEMBER_INDEX: Map<(&Addr, &str), Decimal> = Map::new("member_index");
+/// Settled funds of each (member, denom) that have not been withdrawn yet
+pub const CLAIMABLE: Map<(&Addr, &str), Uint128> = Map::new("claimable");
+/// Funds of each denom credited to members but not withdrawn yet.
+/// This part of the contract balance is excluded from distributions.
+pub const OUTSTANDING: Map<&str, Uint128> = Map::new("outstanding");
+/// Fractions of each denom lost to rounding down when crediting and settling members.
+/// Whole units are released from `OUTSTANDING` so they go out with the next distribution.
+pub const ROUNDING_DUST: Map<&str, Decimal> = Map::new("rounding_dust");
+
+/// The weight each member agreed to be reduced to
+pub const REDUCTION_APPROVALS: Map<&Addr, u64> = Map::new("reduction_approvals");
@@ -3,12 +3,16 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, export_schema_with_title, remove_schemas, schema_for};
 
-use cosmwasm_std::Empty;
 use cw721::{
     AllNftInfoResponse, ApprovalResponse, ApprovalsResponse, ContractInfoResponse, NftInfoResponse,
     NumTokensResponse, OperatorsResponse, OwnerOfResponse, TokensResponse,
 };
-use sg721_imago::msg::{CollectionInfoResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
+use sg721_imago::msg::{
+    CodeUriHistoryResponse, CodeUriResponse, CollectionInfoResponse, ExecuteMsg, InstantiateMsg,
+    IsFinalizedResponse, PendingFinalizationsResponse, QueryMsg, TokenCodeUriResponse,
+    TokenSeedResponse,
+};
+use sg721_imago::state::Metadata;
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -20,8 +24,14 @@ fn main() {
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(CollectionInfoResponse), &out_dir);
+    export_schema(&schema_for!(IsFinalizedResponse), &out_dir);
+    export_schema(&schema_for!(PendingFinalizationsResponse), &out_dir);
+    export_schema(&schema_for!(TokenSeedResponse), &out_dir);
+    export_schema(&schema_for!(CodeUriResponse), &out_dir);
+    export_schema(&schema_for!(CodeUriHistoryResponse), &out_dir);
+    export_schema(&schema_for!(TokenCodeUriResponse), &out_dir);
     export_schema_with_title(
-        &schema_for!(AllNftInfoResponse<Empty>),
+        &schema_for!(AllNftInfoResponse<Metadata>),
         &out_dir,
         "AllNftInfoResponse",
     );
@@ -30,7 +40,7 @@ fn main() {
     export_schema(&schema_for!(OperatorsResponse), &out_dir);
     export_schema(&schema_for!(ContractInfoResponse), &out_dir);
     export_schema_with_title(
-        &schema_for!(NftInfoResponse<Empty>),
+        &schema_for!(NftInfoResponse<Metadata>),
         &out_dir,
         "NftInfoResponse",
     );
@@ -1,15 +1,18 @@
-use cosmwasm_std::{ Binary, Deps, DepsMut, Empty, Env, MessageInfo, StdResult, to_binary};
+use cosmwasm_std::{ Addr, Binary, Deps, DepsMut, Empty, Env, Event, MessageInfo, Order, StdResult, to_binary};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cw2::set_contract_version;
-use cw721::ContractInfoResponse;
+use cw721::{ContractInfoResponse, TokensResponse};
+use cw721_base::{ExecuteMsg as Cw721ExecuteMsg, MintMsg};
+use cw_storage_plus::Bound;
 use sg_std::StargazeMsgWrapper;
+use sha2::{Digest, Sha256};
 use url::Url;
 
 use crate::ContractError;
 use crate::ContractError::Unauthorized;
-use crate::msg::{CodeUriResponse, CollectionInfoResponse, ExecuteMsg, InstantiateMsg, QueryMsg, RoyaltyInfoResponse};
-use crate::state::{CODE_URI, COLLECTION_INFO, CollectionInfo, FINALIZER, RoyaltyInfo};
+use crate::msg::{CodeUriHistoryResponse, CodeUriResponse, CodeUriVersionResponse, CollectionInfoResponse, ExecuteMsg, InstantiateMsg, IsFinalizedResponse, MigrateMsg, PendingFinalizationResponse, PendingFinalizationsResponse, QueryMsg, RoyaltyInfoResponse, TokenCodeUriResponse, TokenSeedResponse};
+use crate::state::{CODE_URI, CODE_URI_LOCKED, CODE_URI_VERSION, CODE_URI_VERSIONS, CodeUriVersion, COLLECTION_INFO, CollectionInfo, FINALIZED, FINALIZER, Metadata, PENDING_FINALIZATIONS, PendingFinalization, RoyaltyInfo, TOKEN_CODE_VERSIONS};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:sg-721-imago";
@@ -17,15 +20,19 @@ const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 const MAX_DESCRIPTION_LENGTH: u32 = 512;
 
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
 pub const DEV_ADDRESS: &str = "stars1zmqesn4d0gjwhcp2f0j3ptc2agqjcqmuadl6cr";
 
 type Response = cosmwasm_std::Response<StargazeMsgWrapper>;
-pub type Sg721ImagoContract<'a> = cw721_base::Cw721Contract<'a, Empty, StargazeMsgWrapper>;
+pub type Sg721ImagoContract<'a> = cw721_base::Cw721Contract<'a, Metadata, StargazeMsgWrapper>;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
@@ -85,7 +92,7 @@ pub fn instantiate(
     };
 
     COLLECTION_INFO.save(deps.storage, &collection_info)?;
-    CODE_URI.save(deps.storage, &msg.code_uri)?;
+    save_code_uri(deps, &env, msg.code_uri)?;
 
     Ok(Response::default()
         .add_attribute("action", "instantiate")
@@ -105,7 +112,7 @@ fn validate_code_uri(uri: String) -> Option<ContractError> {
     None
 }
 
-fn finalize_token_uri(deps: DepsMut,
+fn finalize_token_uri(mut deps: DepsMut,
                       _env: Env,
                       info: MessageInfo,
                       token_id: String,
@@ -117,25 +124,174 @@ fn finalize_token_uri(deps: DepsMut,
         return Err(Unauthorized {});
     }
 
+    // the first finalization is permanent
+    if FINALIZED.has(deps.storage, &token_id) {
+        return Err(ContractError::Finalized {});
+    }
+
+    set_token_uri(deps.branch(), &token_id, token_uri)?;
+    FINALIZED.save(deps.storage, &token_id, &true)?;
+    PENDING_FINALIZATIONS.remove(deps.storage, &token_id);
+
+    Ok(Response::new()
+        .add_attribute("action", "finalize")
+        .add_attribute("token_id", token_id))
+}
+
+fn finalize_token_uris(mut deps: DepsMut,
+                       _env: Env,
+                       info: MessageInfo,
+                       items: Vec<(String, String)>,
+) -> Result<Response, ContractError> {
+    let finalizer = FINALIZER.load(deps.storage)?;
+
+    if info.sender != finalizer {
+        return Err(Unauthorized {});
+    }
+
+    if items.is_empty() {
+        return Err(ContractError::EmptyBatch {});
+    }
+
+    // validate every item before touching any token
+    let mut token_ids: Vec<&str> = items.iter().map(|(token_id, _)| token_id.as_str()).collect();
+    token_ids.sort_unstable();
+    if let Some(pair) = token_ids.windows(2).find(|pair| pair[0] == pair[1]) {
+        return Err(ContractError::DuplicateTokenId {
+            token_id: pair[0].to_string(),
+        });
+    }
+    for token_id in token_ids {
+        if FINALIZED.has(deps.storage, token_id) {
+            return Err(ContractError::Finalized {});
+        }
+    }
+
+    let mut events: Vec<Event> = Vec::with_capacity(items.len());
+    for (token_id, token_uri) in items {
+        set_token_uri(deps.branch(), &token_id, token_uri.clone())?;
+        FINALIZED.save(deps.storage, &token_id, &true)?;
+        PENDING_FINALIZATIONS.remove(deps.storage, &token_id);
+        events.push(
+            Event::new("finalize_token_uri")
+                .add_attribute("token_id", token_id)
+                .add_attribute("token_uri", token_uri),
+        );
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "finalize_batch")
+        .add_attribute("count", events.len().to_string())
+        .add_events(events))
+}
+
+fn execute_override_token_uri(deps: DepsMut,
+                              _env: Env,
+                              info: MessageInfo,
+                              token_id: String,
+                              token_uri: String,
+) -> Result<Response, ContractError> {
+    let config = COLLECTION_INFO.load(deps.storage)?;
+
+    if info.sender != config.creator {
+        return Err(Unauthorized {});
+    }
+
+    if !FINALIZED.has(deps.storage, &token_id) {
+        return Err(ContractError::NotFinalized {});
+    }
+
+    set_token_uri(deps, &token_id, token_uri.clone())?;
+
+    let event = Event::new("override_token_uri")
+        .add_attribute("sender", info.sender)
+        .add_attribute("token_id", token_id)
+        .add_attribute("token_uri", token_uri);
+
+    Ok(Response::new()
+        .add_attribute("action", "override_token_uri")
+        .add_event(event))
+}
+
+// Every minted token gets a seed in its extension and is queued for the renderer until it is finalized.
+// The seed is always derived here, the minter can't choose it.
+fn execute_mint(mut deps: DepsMut,
+                env: Env,
+                info: MessageInfo,
+                mint_msg: MintMsg<Empty>,
+) -> Result<Response, ContractError> {
+    let token_id = mint_msg.token_id.clone();
+    let owner = deps.api.addr_validate(&mint_msg.owner)?;
+    let seed = token_seed(&env, &token_id, &owner);
+    let mint_msg = MintMsg {
+        token_id: mint_msg.token_id,
+        owner: mint_msg.owner,
+        token_uri: mint_msg.token_uri,
+        extension: Metadata { seed: seed.clone() },
+    };
+    let res = Sg721ImagoContract::default()
+        .execute(deps.branch(), env.clone(), info, Cw721ExecuteMsg::Mint(mint_msg))?;
+
+    TOKEN_CODE_VERSIONS.save(deps.storage, &token_id, &CODE_URI_VERSION.load(deps.storage)?)?;
+    // minted tokens are rendered from the current code_uri, so it can't change after the first mint
+    CODE_URI_LOCKED.save(deps.storage, &true)?;
+    let pending = PendingFinalization {
+        height: env.block.height,
+        time: env.block.time,
+        tx_index: env.transaction.as_ref().map(|tx| tx.index),
+        seed,
+    };
+    PENDING_FINALIZATIONS.save(deps.storage, &token_id, &pending)?;
+
+    Ok(res.add_attribute("seed", pending.seed))
+}
+
+fn execute_burn(mut deps: DepsMut,
+                env: Env,
+                info: MessageInfo,
+                token_id: String,
+) -> Result<Response, ContractError> {
+    let res = Sg721ImagoContract::default()
+        .execute(deps.branch(), env, info, ExecuteMsg::Burn { token_id: token_id.clone() }.into())?;
+    PENDING_FINALIZATIONS.remove(deps.storage, &token_id);
+    TOKEN_CODE_VERSIONS.remove(deps.storage, &token_id);
+    Ok(res)
+}
+
+// Input hash for the generative code, reproducible by anyone from the mint tx.
+// Contracts can't read the block hash, so it is derived from the mint block, token id and
+// the address the token is minted to.
+pub fn token_seed(env: &Env, token_id: &str, owner: &Addr) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(env.block.height.to_be_bytes());
+    hasher.update(env.block.time.nanos().to_be_bytes());
+    hasher.update(env.transaction.as_ref().map(|tx| tx.index).unwrap_or(0).to_be_bytes());
+    hasher.update(token_id.as_bytes());
+    hasher.update(owner.as_bytes());
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn set_token_uri(deps: DepsMut, token_id: &str, token_uri: String) -> Result<(), ContractError> {
     Sg721ImagoContract::default()
         .tokens
-        .update(deps.storage, &token_id, |token| match token {
+        .update(deps.storage, token_id, |token| match token {
             Some(mut token_info) => {
                 token_info.token_uri = Some(token_uri);
-                token_info.extension = Empty {};
                 Ok(token_info)
             }
             None => Err(ContractError::TokenNotFound {
                 got: token_id.to_string(),
             }),
         })?;
-
-    Ok(Response::new()
-        .add_attribute("action", "finalize"))
+    Ok(())
 }
 
 fn execute_set_code_uri(deps: DepsMut,
-                        _env: Env,
+                        env: Env,
                         info: MessageInfo,
                         uri: String,
 ) -> Result<Response, ContractError> {
@@ -145,15 +301,51 @@ fn execute_set_code_uri(deps: DepsMut,
         return Err(Unauthorized {});
     }
 
+    if CODE_URI_LOCKED.may_load(deps.storage)?.unwrap_or(false) {
+        return Err(ContractError::CodeUriLocked {});
+    }
+
     let maybe_err = validate_code_uri(uri.clone());
     if let Some(..) = maybe_err {
         return Err(maybe_err.unwrap());
     }
 
-    CODE_URI.save(deps.storage, &uri)?;
+    let version = save_code_uri(deps, &env, uri)?;
 
     Ok(Response::new()
-        .add_attribute("action", "set_code_uri"))
+        .add_attribute("action", "set_code_uri")
+        .add_attribute("version", version.to_string()))
+}
+
+fn execute_lock_code_uri(deps: DepsMut,
+                         _env: Env,
+                         info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = COLLECTION_INFO.load(deps.storage)?;
+    let minter = Sg721ImagoContract::default().minter.load(deps.storage)?;
+
+    if info.sender != config.creator && info.sender != minter {
+        return Err(Unauthorized {});
+    }
+
+    // locking twice is a no-op so the minter can always lock on sellout
+    CODE_URI_LOCKED.save(deps.storage, &true)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "lock_code_uri")
+        .add_attribute("version", CODE_URI_VERSION.load(deps.storage)?.to_string()))
+}
+
+// Appends the code_uri to the history as the new current version
+fn save_code_uri(deps: DepsMut, env: &Env, code_uri: String) -> StdResult<u32> {
+    let version = CODE_URI_VERSION.may_load(deps.storage)?.unwrap_or(0) + 1;
+    CODE_URI_VERSIONS.save(deps.storage, version, &CodeUriVersion {
+        code_uri: code_uri.clone(),
+        height: env.block.height,
+    })?;
+    CODE_URI_VERSION.save(deps.storage, &version)?;
+    CODE_URI.save(deps.storage, &code_uri)?;
+    Ok(version)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -165,18 +357,60 @@ pub fn execute(
 ) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::FinalizeTokenUri { token_id, token_uri } => finalize_token_uri(deps, env, info, token_id, token_uri),
+        ExecuteMsg::FinalizeTokenUris { items } => finalize_token_uris(deps, env, info, items),
+        ExecuteMsg::OverrideTokenUri { token_id, token_uri } => execute_override_token_uri(deps, env, info, token_id, token_uri),
         ExecuteMsg::SetCodeUri { uri } => execute_set_code_uri(deps, env, info, uri),
+        ExecuteMsg::LockCodeUri {} => execute_lock_code_uri(deps, env, info),
+        ExecuteMsg::Mint(mint_msg) => execute_mint(deps, env, info, mint_msg),
+        ExecuteMsg::Burn { token_id } => execute_burn(deps, env, info, token_id),
         _ => Sg721ImagoContract::default()
             .execute(deps, env, info, msg.into())
             .map_err(ContractError::from),
     }
 }
 
+/// Gives tokens minted before seeds were stored a seed derived from the migration block,
+/// and queues those not yet finalized so `UnfinalizedTokens` lists them.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let contract = Sg721ImagoContract::default();
+    let tokens = contract
+        .tokens
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (token_id, mut token) in tokens {
+        if token.extension.seed.is_empty() {
+            token.extension.seed = token_seed(&env, &token_id, &token.owner);
+            contract.tokens.save(deps.storage, &token_id, &token)?;
+        }
+        if !FINALIZED.has(deps.storage, &token_id)
+            && !PENDING_FINALIZATIONS.has(deps.storage, &token_id)
+        {
+            let pending = PendingFinalization {
+                height: env.block.height,
+                time: env.block.time,
+                tx_index: env.transaction.as_ref().map(|tx| tx.index),
+                seed: token.extension.seed,
+            };
+            PENDING_FINALIZATIONS.save(deps.storage, &token_id, &pending)?;
+        }
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::new().add_attribute("action", "migrate"))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::CollectionInfo {} => to_binary(&query_config(deps)?),
         QueryMsg::CodeUri {} => to_binary(&query_code_uri(deps)?),
+        QueryMsg::CodeUriHistory { start_after, limit } => to_binary(&query_code_uri_history(deps, start_after, limit)?),
+        QueryMsg::TokenCodeUri { token_id } => to_binary(&query_token_code_uri(deps, token_id)?),
+        QueryMsg::IsFinalized { token_id } => to_binary(&query_is_finalized(deps, token_id)?),
+        QueryMsg::TokenSeed { token_id } => to_binary(&query_token_seed(deps, token_id)?),
+        QueryMsg::PendingFinalizations { start_after, limit } => to_binary(&query_pending_finalizations(deps, start_after, limit)?),
+        QueryMsg::UnfinalizedTokens { start_after, limit } => to_binary(&query_unfinalized_tokens(deps, start_after, limit)?),
         _ => Sg721ImagoContract::default().query(deps, env, msg.into()),
     }
 }
@@ -203,12 +437,95 @@ fn query_config(deps: Deps) -> StdResult<CollectionInfoResponse> {
 
 fn query_code_uri(deps: Deps) -> StdResult<CodeUriResponse> {
     let code_uri = CODE_URI.load(deps.storage)?;
+    let version = CODE_URI_VERSION.load(deps.storage)?;
+    let locked = CODE_URI_LOCKED.may_load(deps.storage)?.unwrap_or(false);
 
     Ok(CodeUriResponse {
         code_uri,
+        version,
+        locked,
+    })
+}
+
+fn query_code_uri_history(
+    deps: Deps,
+    start_after: Option<u32>,
+    limit: Option<u32>,
+) -> StdResult<CodeUriHistoryResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let versions = CODE_URI_VERSIONS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(version, v)| CodeUriVersionResponse {
+                version,
+                code_uri: v.code_uri,
+                height: v.height,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(CodeUriHistoryResponse { versions })
+}
+
+fn query_token_code_uri(deps: Deps, token_id: String) -> StdResult<TokenCodeUriResponse> {
+    let version = TOKEN_CODE_VERSIONS.load(deps.storage, &token_id)?;
+    let code_uri = CODE_URI_VERSIONS.load(deps.storage, version)?.code_uri;
+
+    Ok(TokenCodeUriResponse {
+        token_id,
+        version,
+        code_uri,
     })
 }
 
+fn query_is_finalized(deps: Deps, token_id: String) -> StdResult<IsFinalizedResponse> {
+    Ok(IsFinalizedResponse {
+        finalized: FINALIZED.has(deps.storage, &token_id),
+    })
+}
+
+fn query_token_seed(deps: Deps, token_id: String) -> StdResult<TokenSeedResponse> {
+    let token = Sg721ImagoContract::default().tokens.load(deps.storage, &token_id)?;
+    Ok(TokenSeedResponse { token_id, seed: token.extension.seed })
+}
+
+fn query_pending_finalizations(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<PendingFinalizationsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    let pending = PENDING_FINALIZATIONS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(token_id, pending)| PendingFinalizationResponse { token_id, pending }))
+        .collect::<StdResult<_>>()?;
+
+    Ok(PendingFinalizationsResponse { pending })
+}
+
+fn query_unfinalized_tokens(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<TokensResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    // every minted token is pending until finalized, so finalized tokens are never scanned
+    let tokens = PENDING_FINALIZATIONS
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(TokensResponse { tokens })
+}
+
 #[cfg(test)]
 mod tests {
     use cosmwasm_std::{coins, Decimal, from_binary};
@@ -342,16 +659,14 @@ mod tests {
         });
 
         let allowed = mock_info(MINTER, &[]);
-        let _ = Sg721ImagoContract::default()
-            .execute(deps.as_mut(), mock_env(), allowed.clone(), exec_mint_msg.into())
-            .unwrap();
+        let _ = execute(deps.as_mut(), mock_env(), allowed.clone(), exec_mint_msg).unwrap();
 
         let query_msg: QueryMsg = QueryMsg::NftInfo {
             token_id: (&token_id).to_string(),
         };
 
         // confirm response is the same
-        let res: NftInfoResponse<Empty> =
+        let res: NftInfoResponse<Metadata> =
             from_binary(&query(deps.as_ref(), mock_env(), query_msg.clone()).unwrap()).unwrap();
         assert_eq!(res.token_uri, Some(token_uri));
 
@@ -370,13 +685,276 @@ mod tests {
         )
             .unwrap();
 
-        let res: NftInfoResponse<Empty> =
+        let res: NftInfoResponse<Metadata> =
             from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
 
         assert_eq!(
             res.token_uri,
             Some(format!("{}", new_token_uri))
         );
+        // the seed derived at mint survives finalization
+        assert_eq!(
+            res.extension.seed,
+            token_seed(&mock_env(), "1", &Addr::unchecked("medusa"))
+        );
+    }
+
+    #[test]
+    fn finalization_is_permanent() {
+        let mut deps = mock_dependencies();
+        let creator = String::from("creator");
+        let finalizer = String::from("finalizer_address");
+        const MINTER: &str = "minter";
+
+        let msg = InstantiateMsg {
+            name: String::from("collection0"),
+            symbol: String::from("BOBO"),
+            minter: String::from(MINTER),
+            code_uri: "ipfs://abc123".to_string(),
+            collection_info: CollectionInfo {
+                creator: creator.clone(),
+                description: String::from("Stargaze Monkeys"),
+                image: "https://example.com/image.png".to_string(),
+                external_link: None,
+                royalty_info: None,
+            },
+            finalizer: finalizer.to_string(),
+        };
+        let info = mock_info(&creator, &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // mint 3 nfts
+        for token_id in 1..=3 {
+            let exec_mint_msg = ExecuteMsg::Mint(MintMsg::<Empty> {
+                token_id: token_id.to_string(),
+                owner: String::from("medusa"),
+                token_uri: Some("https://imago.com".to_string()),
+                extension: Empty {},
+            });
+            execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), exec_mint_msg).unwrap();
+        }
+
+        let is_finalized = |deps: Deps, token_id: &str| -> bool {
+            let res: IsFinalizedResponse = from_binary(
+                &query(deps, mock_env(), QueryMsg::IsFinalized { token_id: token_id.to_string() }).unwrap(),
+            )
+                .unwrap();
+            res.finalized
+        };
+        assert!(!is_finalized(deps.as_ref(), "2"));
+
+        let finalize_token_uri_msg = ExecuteMsg::FinalizeTokenUri {
+            token_id: "2".to_string(),
+            token_uri: "ipfs://abc123/2".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&finalizer, &[]), finalize_token_uri_msg.clone()).unwrap();
+        assert!(is_finalized(deps.as_ref(), "2"));
+
+        // finalizer can't rewrite a finalized token
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&finalizer, &[]), finalize_token_uri_msg).unwrap_err();
+        assert_eq!(err.to_string(), ContractError::Finalized {}.to_string());
+
+        // only finalized tokens can be overridden, and only by the creator
+        let override_msg = ExecuteMsg::OverrideTokenUri {
+            token_id: "2".to_string(),
+            token_uri: "ipfs://xyz987/2".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&finalizer, &[]), override_msg.clone()).unwrap_err();
+        assert_eq!(err.to_string(), ContractError::Unauthorized {}.to_string());
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&creator, &[]),
+            ExecuteMsg::OverrideTokenUri {
+                token_id: "1".to_string(),
+                token_uri: "ipfs://xyz987/1".to_string(),
+            },
+        )
+            .unwrap_err();
+        assert_eq!(err.to_string(), ContractError::NotFinalized {}.to_string());
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info(&creator, &[]), override_msg).unwrap();
+        assert_eq!(res.events.len(), 1);
+        assert_eq!(res.events[0].ty, "override_token_uri");
+
+        let res: NftInfoResponse<Metadata> = from_binary(
+            &query(deps.as_ref(), mock_env(), QueryMsg::NftInfo { token_id: "2".to_string() }).unwrap(),
+        )
+            .unwrap();
+        assert_eq!(res.token_uri, Some("ipfs://xyz987/2".to_string()));
+
+        // unfinalized tokens skip token 2
+        let res: TokensResponse = from_binary(
+            &query(deps.as_ref(), mock_env(), QueryMsg::UnfinalizedTokens { start_after: None, limit: None }).unwrap(),
+        )
+            .unwrap();
+        assert_eq!(res.tokens, vec!["1".to_string(), "3".to_string()]);
+
+        let res: TokensResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::UnfinalizedTokens { start_after: Some("1".to_string()), limit: Some(1) },
+            )
+                .unwrap(),
+        )
+            .unwrap();
+        assert_eq!(res.tokens, vec!["3".to_string()]);
+    }
+
+    #[test]
+    fn batch_finalization() {
+        let mut deps = mock_dependencies();
+        let finalizer = String::from("finalizer_address");
+        const MINTER: &str = "minter";
+
+        let msg = InstantiateMsg {
+            name: String::from("collection0"),
+            symbol: String::from("BOBO"),
+            minter: String::from(MINTER),
+            code_uri: "ipfs://abc123".to_string(),
+            collection_info: CollectionInfo {
+                creator: String::from("creator"),
+                description: String::from("Stargaze Monkeys"),
+                image: "https://example.com/image.png".to_string(),
+                external_link: None,
+                royalty_info: None,
+            },
+            finalizer: finalizer.to_string(),
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        for token_id in 1..=3 {
+            let exec_mint_msg = ExecuteMsg::Mint(MintMsg::<Empty> {
+                token_id: token_id.to_string(),
+                owner: String::from("medusa"),
+                token_uri: Some("https://imago.com".to_string()),
+                extension: Empty {},
+            });
+            execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), exec_mint_msg).unwrap();
+        }
+
+        let items = |ids: &[&str]| -> Vec<(String, String)> {
+            ids.iter()
+                .map(|id| (id.to_string(), format!("ipfs://abc123/{}", id)))
+                .collect()
+        };
+
+        // only the finalizer
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::FinalizeTokenUris { items: items(&["1"]) },
+        )
+            .unwrap_err();
+        assert_eq!(err.to_string(), ContractError::Unauthorized {}.to_string());
+
+        // duplicates are rejected
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&finalizer, &[]),
+            ExecuteMsg::FinalizeTokenUris { items: items(&["1", "2", "1"]) },
+        )
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ContractError::DuplicateTokenId { token_id: "1".to_string() }.to_string()
+        );
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&finalizer, &[]),
+            ExecuteMsg::FinalizeTokenUris { items: items(&["1", "3"]) },
+        )
+            .unwrap();
+        assert_eq!(res.events.len(), 2);
+        assert_eq!(res.events[0].ty, "finalize_token_uri");
+
+        let res: NftInfoResponse<Metadata> = from_binary(
+            &query(deps.as_ref(), mock_env(), QueryMsg::NftInfo { token_id: "3".to_string() }).unwrap(),
+        )
+            .unwrap();
+        assert_eq!(res.token_uri, Some("ipfs://abc123/3".to_string()));
+
+        // a batch containing an already finalized token is rejected as a whole
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&finalizer, &[]),
+            ExecuteMsg::FinalizeTokenUris { items: items(&["2", "3"]) },
+        )
+            .unwrap_err();
+        assert_eq!(err.to_string(), ContractError::Finalized {}.to_string());
+        let res: IsFinalizedResponse = from_binary(
+            &query(deps.as_ref(), mock_env(), QueryMsg::IsFinalized { token_id: "2".to_string() }).unwrap(),
+        )
+            .unwrap();
+        assert!(!res.finalized);
+    }
+
+    #[test]
+    fn pending_finalizations() {
+        let mut deps = mock_dependencies();
+        let finalizer = String::from("finalizer_address");
+        const MINTER: &str = "minter";
+
+        let msg = InstantiateMsg {
+            name: String::from("collection0"),
+            symbol: String::from("BOBO"),
+            minter: String::from(MINTER),
+            code_uri: "ipfs://abc123".to_string(),
+            collection_info: CollectionInfo {
+                creator: String::from("creator"),
+                description: String::from("Stargaze Monkeys"),
+                image: "https://example.com/image.png".to_string(),
+                external_link: None,
+                royalty_info: None,
+            },
+            finalizer: finalizer.to_string(),
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        for token_id in 1..=2 {
+            let exec_mint_msg = ExecuteMsg::Mint(MintMsg::<Empty> {
+                token_id: token_id.to_string(),
+                owner: String::from("medusa"),
+                token_uri: Some("https://imago.com".to_string()),
+                extension: Empty {},
+            });
+            execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), exec_mint_msg).unwrap();
+        }
+
+        let query_pending = |deps: Deps| -> PendingFinalizationsResponse {
+            from_binary(
+                &query(deps, mock_env(), QueryMsg::PendingFinalizations { start_after: None, limit: None }).unwrap(),
+            )
+                .unwrap()
+        };
+        let res = query_pending(deps.as_ref());
+        assert_eq!(res.pending.len(), 2);
+        assert_eq!(res.pending[0].token_id, "1");
+        assert_eq!(res.pending[0].pending.height, mock_env().block.height);
+        assert_eq!(res.pending[0].pending.seed.len(), 64);
+        assert_ne!(res.pending[0].pending.seed, res.pending[1].pending.seed);
+
+        let finalize_token_uri_msg = ExecuteMsg::FinalizeTokenUri {
+            token_id: "1".to_string(),
+            token_uri: "ipfs://abc123/1".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&finalizer, &[]), finalize_token_uri_msg).unwrap();
+
+        let res = query_pending(deps.as_ref());
+        assert_eq!(res.pending.len(), 1);
+        assert_eq!(res.pending[0].token_id, "2");
+
+        // burnt tokens leave the queue
+        execute(deps.as_mut(), mock_env(), mock_info("medusa", &[]), ExecuteMsg::Burn { token_id: "2".to_string() }).unwrap();
+        let res = query_pending(deps.as_ref());
+        assert!(res.pending.is_empty());
     }
 
     #[test]
@@ -433,8 +1011,186 @@ mod tests {
         let res: CodeUriResponse =
             from_binary(&query(deps.as_ref(), mock_env(), query_msg.clone()).unwrap()).unwrap();
         assert_eq!(res.code_uri, new_code_uri.clone());
+        assert_eq!(res.version, 2);
+        assert!(!res.locked);
 
+        // mint against version 2
+        let exec_mint_msg = ExecuteMsg::Mint(MintMsg::<Empty> {
+            token_id: "1".to_string(),
+            owner: String::from("medusa"),
+            token_uri: Some("https://imago.com".to_string()),
+            extension: Empty {},
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), exec_mint_msg).unwrap();
+
+        // the first mint locks the code_uri
+        let res: CodeUriResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert!(res.locked);
 
-        //
+        // only the creator or minter can lock
+        let err = execute(deps.as_mut(), mock_env(), mock_info("medusa", &[]), ExecuteMsg::LockCodeUri {})
+            .unwrap_err();
+        assert_eq!(err.to_string(), ContractError::Unauthorized {}.to_string());
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            allowed,
+            ExecuteMsg::SetCodeUri {
+                uri: "ipfs://def456".to_string(),
+            },
+        )
+            .unwrap_err();
+        assert_eq!(err.to_string(), ContractError::CodeUriLocked {}.to_string());
+
+        let res: CodeUriHistoryResponse = from_binary(
+            &query(deps.as_ref(), mock_env(), QueryMsg::CodeUriHistory { start_after: None, limit: None }).unwrap(),
+        )
+            .unwrap();
+        assert_eq!(
+            res.versions.iter().map(|v| v.code_uri.clone()).collect::<Vec<_>>(),
+            vec![original_code_uri, new_code_uri.clone()]
+        );
+
+        let res: TokenCodeUriResponse = from_binary(
+            &query(deps.as_ref(), mock_env(), QueryMsg::TokenCodeUri { token_id: "1".to_string() }).unwrap(),
+        )
+            .unwrap();
+        assert_eq!(res.version, 2);
+        assert_eq!(res.code_uri, new_code_uri);
+    }
+
+    #[test]
+    fn migrate_seeds_old_tokens() {
+        let mut deps = mock_dependencies();
+        const MINTER: &str = "minter";
+
+        let msg = InstantiateMsg {
+            name: String::from("collection0"),
+            symbol: String::from("BOBO"),
+            minter: String::from(MINTER),
+            code_uri: "ipfs://abc123".to_string(),
+            collection_info: CollectionInfo {
+                creator: String::from("creator"),
+                description: String::from("Stargaze Monkeys"),
+                image: "https://example.com/image.png".to_string(),
+                external_link: None,
+                royalty_info: None,
+            },
+            finalizer: String::from("finalizer_address"),
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // minted before seeds were stored, with an empty extension
+        let legacy_mint_msg = Cw721ExecuteMsg::Mint(MintMsg::<Empty> {
+            token_id: "1".to_string(),
+            owner: String::from("medusa"),
+            token_uri: Some("https://imago.com".to_string()),
+            extension: Empty {},
+        });
+        cw721_base::Cw721Contract::<Empty, StargazeMsgWrapper>::default()
+            .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), legacy_mint_msg)
+            .unwrap();
+        let exec_mint_msg = ExecuteMsg::Mint(MintMsg::<Empty> {
+            token_id: "2".to_string(),
+            owner: String::from("medusa"),
+            token_uri: Some("https://imago.com".to_string()),
+            extension: Empty {},
+        });
+        execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), exec_mint_msg).unwrap();
+
+        let query_seed = |deps: Deps, token_id: &str| -> String {
+            let res: TokenSeedResponse = from_binary(
+                &query(deps, mock_env(), QueryMsg::TokenSeed { token_id: token_id.to_string() }).unwrap(),
+            )
+                .unwrap();
+            res.seed
+        };
+
+        // old tokens can still be read and moved
+        let res: NftInfoResponse<Metadata> = from_binary(
+            &query(deps.as_ref(), mock_env(), QueryMsg::NftInfo { token_id: "1".to_string() }).unwrap(),
+        )
+            .unwrap();
+        assert_eq!(res.extension.seed, "");
+        let transfer_msg = ExecuteMsg::TransferNft {
+            recipient: String::from("perseus"),
+            token_id: "1".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("medusa", &[]), transfer_msg).unwrap();
+
+        let minted_seed = query_seed(deps.as_ref(), "2");
+        let mut env = mock_env();
+        env.block.height += 100;
+        migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+
+        assert_eq!(
+            query_seed(deps.as_ref(), "1"),
+            token_seed(&env, "1", &Addr::unchecked("perseus"))
+        );
+        assert_eq!(query_seed(deps.as_ref(), "2"), minted_seed);
+    }
+
+    #[test]
+    fn migrate_lists_old_unfinalized_tokens() {
+        let mut deps = mock_dependencies();
+        let finalizer = String::from("finalizer_address");
+        const MINTER: &str = "minter";
+
+        let msg = InstantiateMsg {
+            name: String::from("collection0"),
+            symbol: String::from("BOBO"),
+            minter: String::from(MINTER),
+            code_uri: "ipfs://abc123".to_string(),
+            collection_info: CollectionInfo {
+                creator: String::from("creator"),
+                description: String::from("Stargaze Monkeys"),
+                image: "https://example.com/image.png".to_string(),
+                external_link: None,
+                royalty_info: None,
+            },
+            finalizer: finalizer.to_string(),
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // minted before the pending index existed
+        for token_id in 1..=3 {
+            let legacy_mint_msg = Cw721ExecuteMsg::Mint(MintMsg::<Empty> {
+                token_id: token_id.to_string(),
+                owner: String::from("medusa"),
+                token_uri: Some("https://imago.com".to_string()),
+                extension: Empty {},
+            });
+            cw721_base::Cw721Contract::<Empty, StargazeMsgWrapper>::default()
+                .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), legacy_mint_msg)
+                .unwrap();
+        }
+        FINALIZED.save(deps.as_mut().storage, "2", &true).unwrap();
+
+        let query_unfinalized = |deps: Deps| -> TokensResponse {
+            from_binary(
+                &query(deps, mock_env(), QueryMsg::UnfinalizedTokens { start_after: None, limit: None }).unwrap(),
+            )
+                .unwrap()
+        };
+        assert!(query_unfinalized(deps.as_ref()).tokens.is_empty());
+
+        let mut env = mock_env();
+        env.block.height += 100;
+        migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+
+        assert_eq!(query_unfinalized(deps.as_ref()).tokens, vec!["1".to_string(), "3".to_string()]);
+        let pending = PENDING_FINALIZATIONS.load(deps.as_ref().storage, "1").unwrap();
+        assert_eq!(pending.height, env.block.height);
+        assert_eq!(pending.seed, token_seed(&env, "1", &Addr::unchecked("medusa")));
+
+        // finalizing takes them off the list
+        let finalize_token_uri_msg = ExecuteMsg::FinalizeTokenUri {
+            token_id: "1".to_string(),
+            token_uri: "ipfs://abc123/1".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&finalizer, &[]), finalize_token_uri_msg).unwrap();
+        assert_eq!(query_unfinalized(deps.as_ref()).tokens, vec!["3".to_string()]);
     }
 }
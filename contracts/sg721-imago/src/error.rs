@@ -18,6 +18,9 @@ pub enum ContractError {
     #[error("Invalid code_uri")]
     InvalidCodeUri {},
 
+    #[error("code_uri is locked")]
+    CodeUriLocked {},
+
     #[error("token_id already claimed")]
     Claimed {},
 
@@ -44,7 +47,16 @@ pub enum ContractError {
 
     #[error("token_id already finalized")]
     Finalized {},
-    
+
+    #[error("token_id not finalized")]
+    NotFinalized {},
+
+    #[error("No tokens to finalize")]
+    EmptyBatch {},
+
+    #[error("Duplicate token id {token_id}")]
+    DuplicateTokenId { token_id: String },
+
     #[error["Token id not found {got}"]]
     TokenNotFound { got: String },
 
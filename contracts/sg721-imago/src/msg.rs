@@ -1,4 +1,4 @@
-use crate::{state::CollectionInfo, ContractError};
+use crate::{state::CollectionInfo, state::Metadata, state::PendingFinalization, ContractError};
 use cosmwasm_std::{Binary, Decimal, Empty};
 use cw721_base::msg::QueryMsg as Cw721QueryMsg;
 use cw721_base::{ExecuteMsg as Cw721ExecuteMsg, MintMsg};
@@ -16,6 +16,9 @@ pub struct InstantiateMsg {
     pub collection_info: CollectionInfo<RoyaltyInfoResponse>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct RoyaltyInfoResponse {
     pub payment_address: String,
@@ -68,13 +71,26 @@ pub enum ExecuteMsg {
     /// Burn an NFT the sender has access to
     Burn { token_id: String },
     
+    /// Set the final token_uri, can only be called once per token by the finalizer
     FinalizeTokenUri { token_id: String, token_uri: String },
+
+    /// Finalize many (token_id, token_uri) pairs at once, can only be called by the finalizer.
+    /// Either every item is applied or none are.
+    FinalizeTokenUris { items: Vec<(String, String)> },
+
+    /// Replace the token_uri of an already finalized token, can only be called by the creator
+    OverrideTokenUri { token_id: String, token_uri: String },
     
+    /// Replace the code_uri with a new version, can only be called by the creator until locked.
+    /// Minting the first token locks it.
     SetCodeUri { uri: String },
+
+    /// Freeze the code_uri for good, can be called by the creator or the minter
+    LockCodeUri {},
 }
 
-impl From<ExecuteMsg> for Cw721ExecuteMsg<Empty> {
-    fn from(msg: ExecuteMsg) -> Cw721ExecuteMsg<Empty> {
+impl From<ExecuteMsg> for Cw721ExecuteMsg<Metadata> {
+    fn from(msg: ExecuteMsg) -> Cw721ExecuteMsg<Metadata> {
         match msg {
             ExecuteMsg::TransferNft {
                 recipient,
@@ -108,7 +124,6 @@ impl From<ExecuteMsg> for Cw721ExecuteMsg<Empty> {
                 Cw721ExecuteMsg::ApproveAll { operator, expires }
             }
             ExecuteMsg::RevokeAll { operator } => Cw721ExecuteMsg::RevokeAll { operator },
-            ExecuteMsg::Mint(mint_msg) => Cw721ExecuteMsg::Mint(mint_msg),
             ExecuteMsg::Burn { token_id } => Cw721ExecuteMsg::Burn { token_id },
             _ => unreachable!("cannot convert {:?} to Cw721ExecuteMsg", msg),
         }
@@ -158,6 +173,27 @@ pub enum QueryMsg {
     Minter {},
     CollectionInfo {},
     CodeUri {},
+    CodeUriHistory {
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
+    TokenCodeUri {
+        token_id: String,
+    },
+    IsFinalized {
+        token_id: String,
+    },
+    UnfinalizedTokens {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    PendingFinalizations {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    TokenSeed {
+        token_id: String,
+    },
 }
 
 impl From<QueryMsg> for Cw721QueryMsg {
@@ -237,4 +273,47 @@ pub struct CollectionInfoResponse {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct CodeUriResponse {
     pub code_uri: String,
+    pub version: u32,
+    pub locked: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CodeUriVersionResponse {
+    pub version: u32,
+    pub code_uri: String,
+    pub height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CodeUriHistoryResponse {
+    pub versions: Vec<CodeUriVersionResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenCodeUriResponse {
+    pub token_id: String,
+    pub version: u32,
+    pub code_uri: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsFinalizedResponse {
+    pub finalized: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingFinalizationResponse {
+    pub token_id: String,
+    pub pending: PendingFinalization,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingFinalizationsResponse {
+    pub pending: Vec<PendingFinalizationResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenSeedResponse {
+    pub token_id: String,
+    pub seed: String,
 }
@@ -1,5 +1,5 @@
-use cosmwasm_std::{Addr, Decimal};
-use cw_storage_plus::{Item};
+use cosmwasm_std::{Addr, Decimal, Timestamp};
+use cw_storage_plus::{Item, Map};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -18,6 +18,43 @@ pub struct RoyaltyInfo {
     pub share: Decimal,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Metadata {
+    /// hex encoded sha256 input hash for the generative code, set at mint and never changed.
+    /// Empty on tokens minted before seeds were stored, until the contract is migrated.
+    #[serde(default)]
+    pub seed: String,
+}
+
 pub const CODE_URI: Item<String> = Item::new("code_uri");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CodeUriVersion {
+    pub code_uri: String,
+    pub height: u64,
+}
+
+// every code_uri the collection has used, keyed by version starting at 1
+pub const CODE_URI_VERSIONS: Map<u32, CodeUriVersion> = Map::new("code_uri_versions");
+pub const CODE_URI_VERSION: Item<u32> = Item::new("code_uri_version");
+// once locked the code_uri can never change again
+pub const CODE_URI_LOCKED: Item<bool> = Item::new("code_uri_locked");
+// code version each token was minted against
+pub const TOKEN_CODE_VERSIONS: Map<&str, u32> = Map::new("token_code_versions");
 pub const COLLECTION_INFO: Item<CollectionInfo<RoyaltyInfo>> = Item::new("collection_info");
-pub const FINALIZER: Item<Addr> = Item::new("finalizer");
\ No newline at end of file
+pub const FINALIZER: Item<Addr> = Item::new("finalizer");
+// token ids whose token_uri has been finalized
+pub const FINALIZED: Map<&str, bool> = Map::new("finalized");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingFinalization {
+    pub height: u64,
+    pub time: Timestamp,
+    pub tx_index: Option<u32>,
+    /// hex encoded sha256 seed the token is rendered from
+    pub seed: String,
+}
+
+// minted tokens waiting for the renderer, removed once finalized or burnt.
+// Also the index of unfinalized tokens.
+pub const PENDING_FINALIZATIONS: Map<&str, PendingFinalization> = Map::new("pending_finalizations");
\ No newline at end of file
@@ -7,14 +7,16 @@ use sg1::checked_fair_burn;
 use sg_std::{Response, StargazeMsgWrapper};
 
 use crate::ContractError;
-use cw721::ContractInfoResponse;
-use cw721_base::ContractError as BaseError;
+use cw721::{AllNftInfoResponse, ContractInfoResponse, Cw721Query, NftInfoResponse};
 use url::Url;
 
 use crate::msg::{
-    CollectionInfoResponse, ExecuteMsg, InstantiateMsg, QueryMsg, RoyaltyInfoResponse,
+    CollectionInfoResponse, ExecuteMsg, InstantiateMsg, ProvenanceResponse, QueryMsg,
+    RoyaltyInfoResponse,
+};
+use crate::state::{
+    CollectionInfo, RoyaltyInfo, StartingIndex, COLLECTION_INFO, PROVENANCE_HASH, STARTING_INDEX,
 };
-use crate::state::{CollectionInfo, RoyaltyInfo, COLLECTION_INFO};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:sg-721";
@@ -71,6 +73,11 @@ pub fn instantiate(
 
     deps.api.addr_validate(&msg.collection_info.creator)?;
 
+    if let Some(ref provenance_hash) = msg.provenance_hash {
+        validate_provenance_hash(provenance_hash)?;
+        PROVENANCE_HASH.save(deps.storage, provenance_hash)?;
+    }
+
     let collection_info = CollectionInfo {
         creator: msg.collection_info.creator,
         description: msg.collection_info.description,
@@ -89,24 +96,145 @@ pub fn instantiate(
         .add_messages(fee_msgs))
 }
 
+// A provenance hash is a hex encoded sha256 digest
+fn validate_provenance_hash(provenance_hash: &str) -> Result<(), ContractError> {
+    if provenance_hash.len() != 64 || !provenance_hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(ContractError::InvalidProvenanceHash {});
+    }
+    Ok(())
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
-) -> Result<Response, BaseError> {
-    Sg721Contract::default().execute(deps, env, info, msg)
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::SetStartingIndex {
+            starting_index,
+            num_tokens,
+        } => execute_set_starting_index(deps, info, starting_index, num_tokens),
+        _ => Sg721Contract::default()
+            .execute(deps, env, info, msg.into())
+            .map_err(ContractError::from),
+    }
+}
+
+pub fn execute_set_starting_index(
+    deps: DepsMut,
+    info: MessageInfo,
+    starting_index: u32,
+    num_tokens: u32,
+) -> Result<Response, ContractError> {
+    let minter = Sg721Contract::default().minter.load(deps.storage)?;
+    if info.sender != minter {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if STARTING_INDEX.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::StartingIndexAlreadySet {});
+    }
+
+    if num_tokens == 0 || starting_index >= num_tokens {
+        return Err(ContractError::InvalidStartingIndex {
+            starting_index,
+            num_tokens,
+        });
+    }
+
+    STARTING_INDEX.save(
+        deps.storage,
+        &StartingIndex {
+            starting_index,
+            num_tokens,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_starting_index")
+        .add_attribute("starting_index", starting_index.to_string())
+        .add_attribute("num_tokens", num_tokens.to_string()))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::CollectionInfo {} => to_binary(&query_config(deps)?),
+        QueryMsg::Provenance {} => to_binary(&query_provenance(deps)?),
+        QueryMsg::NftInfo { token_id } => to_binary(&query_nft_info(deps, token_id)?),
+        QueryMsg::AllNftInfo {
+            token_id,
+            include_expired,
+        } => to_binary(&query_all_nft_info(
+            deps,
+            env,
+            token_id,
+            include_expired.unwrap_or(false),
+        )?),
         _ => Sg721Contract::default().query(deps, env, msg.into()),
     }
 }
 
+fn query_provenance(deps: Deps) -> StdResult<ProvenanceResponse> {
+    let provenance_hash = PROVENANCE_HASH.may_load(deps.storage)?;
+    let starting_index = STARTING_INDEX
+        .may_load(deps.storage)?
+        .map(|s| s.starting_index);
+
+    Ok(ProvenanceResponse {
+        provenance_hash,
+        starting_index,
+    })
+}
+
+fn query_nft_info(deps: Deps, token_id: String) -> StdResult<NftInfoResponse<Empty>> {
+    let mut res = Sg721Contract::default().nft_info(deps, token_id)?;
+    let starting_index = STARTING_INDEX.may_load(deps.storage)?;
+    res.token_uri = shift_token_uri(res.token_uri, starting_index);
+    Ok(res)
+}
+
+fn query_all_nft_info(
+    deps: Deps,
+    env: Env,
+    token_id: String,
+    include_expired: bool,
+) -> StdResult<AllNftInfoResponse<Empty>> {
+    let mut res = Sg721Contract::default().all_nft_info(deps, env, token_id, include_expired)?;
+    let starting_index = STARTING_INDEX.may_load(deps.storage)?;
+    res.info.token_uri = shift_token_uri(res.info.token_uri, starting_index);
+    Ok(res)
+}
+
+// Token uris are minted as `<base_token_uri>/<n>` where n is 1-based.
+// Once the starting index is set, n is offset so the artwork assigned to a token
+// could not have been known by the creator or the buyers before sellout.
+fn shift_token_uri(
+    token_uri: Option<String>,
+    starting_index: Option<StartingIndex>,
+) -> Option<String> {
+    let (token_uri, starting_index) = match (token_uri, starting_index) {
+        (Some(token_uri), Some(starting_index)) => (token_uri, starting_index),
+        (token_uri, _) => return token_uri,
+    };
+
+    let (base, n) = match token_uri.rsplit_once('/') {
+        Some((base, n)) => (base, n),
+        None => return Some(token_uri),
+    };
+    match n.parse::<u64>() {
+        Ok(n) if n >= 1 && n <= starting_index.num_tokens as u64 => {
+            let shifted = (n - 1 + starting_index.starting_index as u64)
+                % starting_index.num_tokens as u64
+                + 1;
+            Some(format!("{}/{}", base, shifted))
+        }
+        _ => Some(token_uri),
+    }
+}
+
 fn query_config(deps: Deps) -> StdResult<CollectionInfoResponse> {
     let info = COLLECTION_INFO.load(deps.storage)?;
 
@@ -134,8 +262,12 @@ mod tests {
     use crate::state::CollectionInfo;
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
     use cosmwasm_std::{coins, from_binary, Decimal};
+    use cw721_base::MintMsg;
     use sg_std::NATIVE_DENOM;
 
+    const PROVENANCE_HASH: &str =
+        "4b6f9ae56d2a3b4c98e0b1fbc9a33f5f0e8c2a1d7e6b5c4d3a2b1c0d9e8f7a6b";
+
     #[test]
     fn proper_initialization_no_royalties() {
         let mut deps = mock_dependencies();
@@ -145,6 +277,7 @@ mod tests {
             name: collection,
             symbol: String::from("BOBO"),
             minter: String::from("minter"),
+            provenance_hash: None,
             collection_info: CollectionInfo {
                 creator: String::from("creator"),
                 description: String::from("Stargaze Monkeys"),
@@ -181,6 +314,7 @@ mod tests {
             name: collection,
             symbol: String::from("BOBO"),
             minter: String::from("minter"),
+            provenance_hash: None,
             collection_info: CollectionInfo {
                 creator: String::from("creator"),
                 description: String::from("Stargaze Monkeys"),
@@ -209,4 +343,149 @@ mod tests {
             value.royalty_info
         );
     }
+
+    #[test]
+    fn invalid_provenance_hash() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            name: String::from("collection0"),
+            symbol: String::from("BOBO"),
+            minter: String::from("minter"),
+            provenance_hash: Some(String::from("not a sha256 digest")),
+            collection_info: CollectionInfo {
+                creator: String::from("creator"),
+                description: String::from("Stargaze Monkeys"),
+                image: "https://example.com/image.png".to_string(),
+                external_link: None,
+                royalty_info: None,
+            },
+        };
+        let info = mock_info("creator", &coins(CREATION_FEE, NATIVE_DENOM));
+
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ContractError::InvalidProvenanceHash {}.to_string()
+        );
+    }
+
+    #[test]
+    fn provenance_and_starting_index() {
+        let mut deps = mock_dependencies();
+
+        let msg = InstantiateMsg {
+            name: String::from("collection0"),
+            symbol: String::from("BOBO"),
+            minter: String::from("minter"),
+            provenance_hash: Some(PROVENANCE_HASH.to_string()),
+            collection_info: CollectionInfo {
+                creator: String::from("creator"),
+                description: String::from("Stargaze Monkeys"),
+                image: "https://example.com/image.png".to_string(),
+                external_link: None,
+                royalty_info: None,
+            },
+        };
+        let info = mock_info("creator", &coins(CREATION_FEE, NATIVE_DENOM));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Provenance {}).unwrap();
+        let value: ProvenanceResponse = from_binary(&res).unwrap();
+        assert_eq!(Some(PROVENANCE_HASH.to_string()), value.provenance_hash);
+        assert_eq!(None, value.starting_index);
+
+        // mint all 3 tokens
+        let minter = mock_info("minter", &[]);
+        for token_id in 1..=3 {
+            let mint_msg = ExecuteMsg::Mint(MintMsg::<Empty> {
+                token_id: token_id.to_string(),
+                owner: String::from("buyer"),
+                token_uri: Some(format!("ipfs://bafybeigdyrzt/{}", token_id)),
+                extension: Empty {},
+            });
+            execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg).unwrap();
+        }
+
+        // only the minter can set the starting index
+        let set_msg = ExecuteMsg::SetStartingIndex {
+            starting_index: 2,
+            num_tokens: 3,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            set_msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), ContractError::Unauthorized {}.to_string());
+
+        // starting index must be less than num tokens
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            minter.clone(),
+            ExecuteMsg::SetStartingIndex {
+                starting_index: 3,
+                num_tokens: 3,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ContractError::InvalidStartingIndex {
+                starting_index: 3,
+                num_tokens: 3
+            }
+            .to_string()
+        );
+
+        execute(deps.as_mut(), mock_env(), minter.clone(), set_msg.clone()).unwrap();
+
+        // starting index can only be set once
+        let err = execute(deps.as_mut(), mock_env(), minter, set_msg).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ContractError::StartingIndexAlreadySet {}.to_string()
+        );
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Provenance {}).unwrap();
+        let value: ProvenanceResponse = from_binary(&res).unwrap();
+        assert_eq!(Some(PROVENANCE_HASH.to_string()), value.provenance_hash);
+        assert_eq!(Some(2), value.starting_index);
+
+        // token uri numbering is offset by the starting index and wraps around
+        for (token_id, expected) in [(1, 3), (2, 1), (3, 2)] {
+            let res = query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::NftInfo {
+                    token_id: token_id.to_string(),
+                },
+            )
+            .unwrap();
+            let value: NftInfoResponse<Empty> = from_binary(&res).unwrap();
+            assert_eq!(
+                Some(format!("ipfs://bafybeigdyrzt/{}", expected)),
+                value.token_uri
+            );
+        }
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::AllNftInfo {
+                token_id: "1".to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+        let value: AllNftInfoResponse<Empty> = from_binary(&res).unwrap();
+        assert_eq!(
+            Some("ipfs://bafybeigdyrzt/3".to_string()),
+            value.info.token_uri
+        );
+        assert_eq!("buyer", value.access.owner);
+    }
 }
@@ -31,6 +31,18 @@ pub enum ContractError {
     #[error("Description too long")]
     DescriptionTooLong {},
 
+    #[error("Invalid provenance hash (must be a hex encoded sha256 digest)")]
+    InvalidProvenanceHash {},
+
+    #[error("Starting index already set")]
+    StartingIndexAlreadySet {},
+
+    #[error("Invalid starting index {starting_index} for {num_tokens} tokens")]
+    InvalidStartingIndex {
+        starting_index: u32,
+        num_tokens: u32,
+    },
+
     #[error("{0}")]
     Payment(#[from] PaymentError),
 
@@ -39,6 +51,20 @@ pub enum ContractError {
 
     #[error("{0}")]
     Parse(#[from] ParseError),
+
+    #[error("{0}")]
+    BaseError(Cw721ContractError),
+}
+
+impl From<Cw721ContractError> for ContractError {
+    fn from(err: Cw721ContractError) -> Self {
+        match err {
+            Cw721ContractError::Unauthorized {} => Self::Unauthorized {},
+            Cw721ContractError::Claimed {} => Self::Claimed {},
+            Cw721ContractError::Expired {} => Self::Expired {},
+            err => Self::BaseError(err),
+        }
+    }
 }
 
 impl From<ContractError> for Cw721ContractError {
@@ -47,6 +73,7 @@ impl From<ContractError> for Cw721ContractError {
             ContractError::Unauthorized {} => Cw721ContractError::Unauthorized {},
             ContractError::Claimed {} => Cw721ContractError::Claimed {},
             ContractError::Expired {} => Cw721ContractError::Expired {},
+            ContractError::BaseError(err) => err,
             _ => unreachable!("cannot convert {:?} to Cw721ContractError", err),
         }
     }
@@ -19,3 +19,12 @@ pub struct RoyaltyInfo {
 }
 
 pub const COLLECTION_INFO: Item<CollectionInfo<RoyaltyInfo>> = Item::new("collection_info");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StartingIndex {
+    pub starting_index: u32,
+    pub num_tokens: u32,
+}
+
+pub const PROVENANCE_HASH: Item<String> = Item::new("provenance_hash");
+pub const STARTING_INDEX: Item<StartingIndex> = Item::new("starting_index");
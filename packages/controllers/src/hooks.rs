@@ -1,16 +1,30 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use sg_std::SubMsg;
 use thiserror::Error;
 
-use cosmwasm_std::{Addr, CustomQuery, Deps, StdError, StdResult, Storage};
-use cw_storage_plus::Item;
+use cosmwasm_std::{
+    Addr, CosmosMsg, CustomQuery, Deps, Order, StdError, StdResult, Storage, SubMsg,
+};
+use cw_storage_plus::{Bound, Item, Map};
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+/// Number of hooks a registry accepts unless created with `Hooks::new_with_max`
+pub const DEFAULT_MAX_HOOKS: u32 = 20;
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct HooksResponse {
     pub hooks: Vec<String>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema, Debug)]
+pub struct HookConfig {
+    /// Gas limit applied to every message sent to this hook
+    pub gas_limit: Option<u64>,
+}
+
 #[derive(Error, Debug, PartialEq)]
 pub enum HookError {
     #[error("{0}")]
@@ -21,52 +35,269 @@ pub enum HookError {
 
     #[error("Given address not registered as a hook")]
     HookNotRegistered {},
+
+    #[error("Cannot register more than {max} hooks")]
+    TooManyHooks { max: u32 },
 }
 
-// store all hook addresses in one item. We cannot have many of them before the contract becomes unusable anyway.
-pub struct Hooks<'a>(Item<'a, Vec<Addr>>);
+// store each hook under its own key, capped so preparing hooks stays affordable
+pub struct Hooks<'a> {
+    storage_key: &'a str,
+    hooks: Map<'a, &'a Addr, HookConfig>,
+    max_hooks: u32,
+}
 
 impl<'a> Hooks<'a> {
     pub const fn new(storage_key: &'a str) -> Self {
-        Hooks(Item::new(storage_key))
+        Self::new_with_max(storage_key, DEFAULT_MAX_HOOKS)
+    }
+
+    pub const fn new_with_max(storage_key: &'a str, max_hooks: u32) -> Self {
+        Hooks {
+            storage_key,
+            hooks: Map::new(storage_key),
+            max_hooks,
+        }
+    }
+
+    /// Moves hooks saved by earlier versions, which kept them all in one `Item<Vec<Addr>>`
+    /// under the same storage key. Call it from `migrate`; it does nothing if there are none.
+    pub fn migrate_from_item(&self, storage: &mut dyn Storage) -> StdResult<()> {
+        let legacy: Item<Vec<Addr>> = Item::new(self.storage_key);
+        if let Some(hooks) = legacy.may_load(storage)? {
+            for addr in hooks {
+                self.hooks.save(storage, &addr, &HookConfig::default())?;
+            }
+            legacy.remove(storage);
+        }
+        Ok(())
     }
 
     pub fn add_hook(&self, storage: &mut dyn Storage, addr: Addr) -> Result<(), HookError> {
-        let mut hooks = self.0.may_load(storage)?.unwrap_or_default();
-        if !hooks.iter().any(|h| h == &addr) {
-            hooks.push(addr);
-        } else {
+        self.add_hook_with_gas_limit(storage, addr, None)
+    }
+
+    pub fn add_hook_with_gas_limit(
+        &self,
+        storage: &mut dyn Storage,
+        addr: Addr,
+        gas_limit: Option<u64>,
+    ) -> Result<(), HookError> {
+        if self.hooks.has(storage, &addr) {
             return Err(HookError::HookAlreadyRegistered {});
         }
-        Ok(self.0.save(storage, &hooks)?)
+        let count = self
+            .hooks
+            .keys(storage, None, None, Order::Ascending)
+            .count();
+        if count >= self.max_hooks as usize {
+            return Err(HookError::TooManyHooks {
+                max: self.max_hooks,
+            });
+        }
+        Ok(self.hooks.save(storage, &addr, &HookConfig { gas_limit })?)
+    }
+
+    pub fn set_gas_limit(
+        &self,
+        storage: &mut dyn Storage,
+        addr: Addr,
+        gas_limit: Option<u64>,
+    ) -> Result<(), HookError> {
+        if !self.hooks.has(storage, &addr) {
+            return Err(HookError::HookNotRegistered {});
+        }
+        Ok(self.hooks.save(storage, &addr, &HookConfig { gas_limit })?)
     }
 
     pub fn remove_hook(&self, storage: &mut dyn Storage, addr: Addr) -> Result<(), HookError> {
-        let mut hooks = self.0.load(storage)?;
-        if let Some(p) = hooks.iter().position(|x| x == &addr) {
-            hooks.remove(p);
-        } else {
+        if !self.hooks.has(storage, &addr) {
             return Err(HookError::HookNotRegistered {});
         }
-        Ok(self.0.save(storage, &hooks)?)
+        self.hooks.remove(storage, &addr);
+        Ok(())
     }
 
-    pub fn prepare_hooks<F: Fn(Addr) -> StdResult<SubMsg>>(
+    /// Builds a message for every hook, applying the hook's gas limit if it has one.
+    /// A failing hook is reported to the caller's `reply` entry point with `reply_id`
+    /// instead of reverting the transaction, so the caller should handle that reply
+    /// and return `Ok` to skip the failed hook.
+    pub fn prepare_hooks<T, F: Fn(Addr) -> StdResult<CosmosMsg<T>>>(
         &self,
         storage: &dyn Storage,
+        reply_id: u64,
         prep: F,
-    ) -> StdResult<Vec<SubMsg>> {
-        self.0
-            .may_load(storage)?
-            .unwrap_or_default()
-            .into_iter()
-            .map(prep)
+    ) -> StdResult<Vec<SubMsg<T>>> {
+        self.hooks
+            .range(storage, None, None, Order::Ascending)
+            .map(|item| {
+                let (addr, config) = item?;
+                let msg = SubMsg::reply_on_error(prep(addr)?, reply_id);
+                Ok(match config.gas_limit {
+                    Some(gas_limit) => msg.with_gas_limit(gas_limit),
+                    None => msg,
+                })
+            })
             .collect()
     }
 
+    /// All registered hooks, at most `max_hooks` of them
     pub fn query_hooks<Q: CustomQuery>(&self, deps: Deps<Q>) -> StdResult<HooksResponse> {
-        let hooks = self.0.may_load(deps.storage)?.unwrap_or_default();
-        let hooks = hooks.into_iter().map(String::from).collect();
+        let hooks = self
+            .hooks
+            .keys(deps.storage, None, None, Order::Ascending)
+            .map(|addr| addr.map(String::from))
+            .collect::<StdResult<_>>()?;
         Ok(HooksResponse { hooks })
     }
+
+    pub fn list_hooks<Q: CustomQuery>(
+        &self,
+        deps: Deps<Q>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<HooksResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_after = start_after
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?;
+        let start = start_after.as_ref().map(Bound::exclusive);
+
+        let hooks = self
+            .hooks
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|addr| addr.map(String::from))
+            .collect::<StdResult<_>>()?;
+        Ok(HooksResponse { hooks })
+    }
+
+    pub fn query_hook_config<Q: CustomQuery>(
+        &self,
+        deps: Deps<Q>,
+        addr: String,
+    ) -> StdResult<HookConfig> {
+        let addr = deps.api.addr_validate(&addr)?;
+        self.hooks.load(deps.storage, &addr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::mock_dependencies;
+    use cosmwasm_std::{Binary, ReplyOn, WasmMsg};
+
+    use super::*;
+
+    const HOOKS: Hooks = Hooks::new_with_max("hooks", 3);
+
+    fn hook_msg(addr: Addr) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: addr.to_string(),
+            msg: Binary::default(),
+            funds: vec![],
+        }
+        .into())
+    }
+
+    #[test]
+    fn add_and_remove_hooks() {
+        let mut deps = mock_dependencies();
+        let storage = deps.as_mut().storage;
+
+        HOOKS.add_hook(storage, Addr::unchecked("hook1")).unwrap();
+        let err = HOOKS
+            .add_hook(storage, Addr::unchecked("hook1"))
+            .unwrap_err();
+        assert_eq!(err, HookError::HookAlreadyRegistered {});
+        HOOKS.add_hook(storage, Addr::unchecked("hook2")).unwrap();
+        HOOKS.add_hook(storage, Addr::unchecked("hook3")).unwrap();
+
+        // the registry is capped
+        let err = HOOKS
+            .add_hook(storage, Addr::unchecked("hook4"))
+            .unwrap_err();
+        assert_eq!(err, HookError::TooManyHooks { max: 3 });
+
+        HOOKS
+            .remove_hook(storage, Addr::unchecked("hook2"))
+            .unwrap();
+        let err = HOOKS
+            .remove_hook(storage, Addr::unchecked("hook2"))
+            .unwrap_err();
+        assert_eq!(err, HookError::HookNotRegistered {});
+
+        let res = HOOKS.query_hooks(deps.as_ref()).unwrap();
+        assert_eq!(res.hooks, vec!["hook1".to_string(), "hook3".to_string()]);
+    }
+
+    #[test]
+    fn list_hooks_paginates() {
+        let mut deps = mock_dependencies();
+        for addr in ["hook1", "hook2", "hook3"] {
+            HOOKS
+                .add_hook(deps.as_mut().storage, Addr::unchecked(addr))
+                .unwrap();
+        }
+
+        let res = HOOKS.list_hooks(deps.as_ref(), None, Some(2)).unwrap();
+        assert_eq!(res.hooks, vec!["hook1".to_string(), "hook2".to_string()]);
+        let res = HOOKS
+            .list_hooks(deps.as_ref(), Some("hook2".to_string()), Some(2))
+            .unwrap();
+        assert_eq!(res.hooks, vec!["hook3".to_string()]);
+    }
+
+    #[test]
+    fn prepare_hooks_replies_on_error() {
+        let mut deps = mock_dependencies();
+        let storage = deps.as_mut().storage;
+        HOOKS.add_hook(storage, Addr::unchecked("hook1")).unwrap();
+        HOOKS
+            .add_hook_with_gas_limit(storage, Addr::unchecked("hook2"), Some(100_000))
+            .unwrap();
+
+        let msgs = HOOKS.prepare_hooks(storage, 7, hook_msg).unwrap();
+        assert_eq!(msgs.len(), 2);
+        for msg in &msgs {
+            assert_eq!(msg.id, 7);
+            assert_eq!(msg.reply_on, ReplyOn::Error);
+        }
+        assert_eq!(msgs[0].gas_limit, None);
+        assert_eq!(msgs[1].gas_limit, Some(100_000));
+
+        // the gas limit can be changed later, but only for registered hooks
+        HOOKS
+            .set_gas_limit(storage, Addr::unchecked("hook2"), None)
+            .unwrap();
+        let msgs = HOOKS.prepare_hooks(storage, 7, hook_msg).unwrap();
+        assert_eq!(msgs[1].gas_limit, None);
+        let err = HOOKS
+            .set_gas_limit(storage, Addr::unchecked("hook3"), None)
+            .unwrap_err();
+        assert_eq!(err, HookError::HookNotRegistered {});
+    }
+
+    #[test]
+    fn migrate_from_item() {
+        let mut deps = mock_dependencies();
+        let storage = deps.as_mut().storage;
+        let legacy: Item<Vec<Addr>> = Item::new("hooks");
+        legacy
+            .save(
+                storage,
+                &vec![Addr::unchecked("hook1"), Addr::unchecked("hook2")],
+            )
+            .unwrap();
+
+        HOOKS.migrate_from_item(storage).unwrap();
+        assert!(legacy.may_load(storage).unwrap().is_none());
+        let res = HOOKS.query_hooks(deps.as_ref()).unwrap();
+        assert_eq!(res.hooks, vec!["hook1".to_string(), "hook2".to_string()]);
+
+        // running it again changes nothing
+        HOOKS.migrate_from_item(deps.as_mut().storage).unwrap();
+        let res = HOOKS.query_hooks(deps.as_ref()).unwrap();
+        assert_eq!(res.hooks, vec!["hook1".to_string(), "hook2".to_string()]);
+    }
 }
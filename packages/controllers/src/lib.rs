@@ -1,3 +1,3 @@
 mod hooks;
 
-pub use hooks::{HookError, Hooks, HooksResponse};
+pub use hooks::{HookConfig, HookError, Hooks, HooksResponse, DEFAULT_MAX_HOOKS};